@@ -6,15 +6,30 @@ extern crate stdweb;
 use papito_dom::prelude::VNode;
 use papito_dom::{comp, h, Component};
 #[cfg(target_arch = "wasm32")]
-use stdweb::web::{document, Element, INonElementParentNode};
+use stdweb::web::{document, Element, Node, INonElementParentNode};
+#[cfg(target_arch = "wasm32")]
+use papito_dom::{DOMRender, RenderRequest, StdwebBackend, SavedStates, Mismatch, hydrate};
+#[cfg(target_arch = "wasm32")]
+use stdweb::unstable::TryInto;
+#[cfg(target_arch = "wasm32")]
+use std::collections::HashMap;
 #[cfg(target_arch = "wasm32")]
-use papito_dom::{DOMRender, RenderRequest};
 use std::ops::Deref;
+#[cfg(not(target_arch = "wasm32"))]
+use papito_dom::Document;
 
 pub mod prelude {
     pub use papito_dom::{Lifecycle, Render};
 }
 
+/// The items the `html!` templates expand to, so that an app only needs to depend on `papito`.
+#[doc(hidden)]
+pub mod __codegen {
+    pub use papito_dom::*;
+    #[cfg(target_arch = "wasm32")]
+    pub use stdweb::web::event;
+}
+
 pub struct App {
     vdom: VNode,
     #[cfg(target_arch = "wasm32")]
@@ -39,13 +54,37 @@ impl App {
         }
     }
 
+    /// The html document to be rendered on the server, with the app mounted within the element with
+    /// the `root_id`. The same id is to be passed to `render` or `hydrate` on the client.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn document(self, root_id: &str) -> Document {
+        Document::new(self.vdom, root_id.to_string())
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn render<T: Into<AppRoot>>(self, app_root: T) {
+        let app_root: Node = app_root.into().0.into();
+        self.run(app_root);
+    }
+
+    /// Adopts the markup rendered by the server within the `app_root`, instead of rendering it
+    /// anew. The components are created with the states they saved on the server. Gives the
+    /// mismatches with the first render, which are warned about in the console and patched.
+    #[cfg(target_arch = "wasm32")]
+    pub fn hydrate<T: Into<AppRoot>>(self, app_root: T) -> Vec<Mismatch> {
+        let app_root: Node = app_root.into().0.into();
+        let App { vdom, render_req } = self;
+        let (vdom, mismatches) = hydrate(vdom, &StdwebBackend, &app_root, saved_states(), render_req.sender());
+        App { vdom, render_req }.run(app_root);
+        mismatches
+    }
+
     #[cfg(target_arch = "wasm32")]
-    pub fn render<T: Into<AppRoot>>(mut self, app_root: T) {
-        let app_root = app_root.into();
+    fn run(mut self, app_root: Node) {
         // Re-renders on requests from the components
         let rerender = move |initial_render: bool| {
             if initial_render || self.render_req.receive() {
-                self.vdom.dom_render(&app_root, None, self.render_req.sender());
+                self.vdom.dom_render(&StdwebBackend, &app_root, None, self.render_req.sender());
             }
             js! { @(no_return)
                 window.__is_rendering__ = false;
@@ -67,6 +106,20 @@ impl App {
     }
 }
 
+/// The states saved by the components on the server, within the document head.
+#[cfg(target_arch = "wasm32")]
+fn saved_states() -> SavedStates {
+    let states: HashMap<String, String> = js!(
+        var states = window.__papito_state__ || {};
+        var saved = {};
+        for (var key in states) {
+            saved[key] = JSON.stringify(states[key]);
+        }
+        return saved;
+    ).try_into().unwrap();
+    states.into_iter().collect()
+}
+
 #[cfg(target_arch = "wasm32")]
 pub struct AppRoot(Element);
 
@@ -1,14 +1,17 @@
 use quote::Tokens;
 use syn::{Attribute, Field, Fields, Ident, Item, ItemStruct, Path, Type, Visibility};
+use proc_macro2::{Span, TokenNode, TokenStream, TokenTree, Delimiter};
+use error::{Error, Result};
 
-pub fn quote(item: Item) -> Tokens {
+pub fn quote(metadata: TokenStream, item: Item) -> Result<Tokens> {
+    let save_state = parse_save_state(metadata)?;
     match item {
-        Item::Struct(item_struct) => {
-            let mut component_data = ComponentData::parse(&item_struct);
-            component_data.quote()
+        Item::Struct(ref item_struct) => {
+            let mut component_data = ComponentData::parse(item_struct, save_state)?;
+            Ok(component_data.quote())
         }
-        _ => {
-            panic!("`#[component]` can only be used on a struct");
+        ref item => {
+            Err(Error::spanned(item, "`#[component]` can only be used on a struct"))
         }
     }
 }
@@ -20,22 +23,25 @@ struct ComponentData {
     data: Option<Ident>,
     props: Option<Ident>,
     fields: DataFields,
+    /// Whether the states are handed off from the server to the client
+    save_state: bool,
 }
 
 impl ComponentData {
-    fn parse(item: &ItemStruct) -> ComponentData {
-        let fields = DataFields::parse(&item.fields);
+    fn parse(item: &ItemStruct, save_state: bool) -> Result<ComponentData> {
+        let fields = DataFields::parse(&item.fields)?;
         let component = item.ident.clone();
         let attrs = item.attrs.clone();
         let vis = item.vis.clone();
-        ComponentData {
+        Ok(ComponentData {
             attrs,
             vis,
             data: None,
             props: None,
             component,
             fields,
-        }
+            save_state,
+        })
     }
 
     fn quote(&mut self) -> Tokens {
@@ -44,6 +50,7 @@ impl ComponentData {
         let data_impl = self.quote_data_impl();
         let component_struct = self.quote_component_struct();
         let component_impl = self.quote_component_impl();
+        let watchers_trait = self.quote_watchers_trait();
         let impl_component_trait = self.quote_impl_component_trait();
         quote! {
             #component_struct
@@ -56,10 +63,31 @@ impl ComponentData {
 
             #component_impl
 
+            #watchers_trait
+
             #impl_component_trait
         }
     }
 
+    /// The watchers of the fields, which do nothing. A `#[watch(field)]` method overrides one of
+    /// them with an inherent method of the same name.
+    fn quote_watchers_trait(&self) -> Tokens {
+        if self.data.is_some() {
+            let component = &self.component;
+            let watchers = watchers_trait_name(component);
+            let watch_fns = self.fields.quote_watch_fns();
+            quote! {
+                trait #watchers {
+                    #watch_fns
+                }
+
+                impl #watchers for #component {}
+            }
+        } else {
+            quote!()
+        }
+    }
+
     fn quote_component_struct(&self) -> Tokens {
         let attrs = &self.attrs;
         let vis = &self.vis;
@@ -69,19 +97,36 @@ impl ComponentData {
                 #(#attrs)*
                 #vis struct #component {
                     _data: ::std::rc::Rc<::std::cell::RefCell<#data>>,
-                    _notifier: Box<Fn()>
+                    _notifier: ::std::rc::Rc<Fn()>
                 }
 
                 impl #component {
                     fn _notify(&self) {
                         (self._notifier)();
                     }
+
+                    // A handle sharing the same data, used by the `#[events]` listeners
+                    #[allow(dead_code)]
+                    fn _handle(&self) -> #component {
+                        #component {
+                            _data: self._data.clone(),
+                            _notifier: self._notifier.clone()
+                        }
+                    }
                 }
             }
         } else {
             quote! {
                 #(#attrs)*
                 #vis struct #component;
+
+                impl #component {
+                    // Used by the `#[events]` listeners
+                    #[allow(dead_code)]
+                    fn _handle(&self) -> #component {
+                        #component
+                    }
+                }
             }
         }
     }
@@ -92,10 +137,21 @@ impl ComponentData {
             let props = Ident::from(format!("_{}Props", &self.component));
             self.props = Some(props);
             let props = self.props.as_ref().unwrap();
+            // The required props are `Required`, so that the props are `Default` to be partially
+            // constructed with `..`, which fails to compile when any of them is left out.
+            let props_default = self.fields.quote_props_default();
             quote! {
                 struct #props {
                     #props_fields
                 }
+
+                impl Default for #props {
+                    fn default() -> Self {
+                        #props {
+                            #props_default
+                        }
+                    }
+                }
             }
         } else {
             quote!()
@@ -110,7 +166,9 @@ impl ComponentData {
             let data = self.data.as_ref().unwrap();
             quote! {
                 struct #data {
-                    #data_fields
+                    #data_fields,
+                    // The results of the `#[computed]` methods
+                    _computed: ::papito_dom::ComputedCache
                 }
             }
         } else {
@@ -137,7 +195,7 @@ impl ComponentData {
     fn quote_component_impl(&self) -> Tokens {
         if self.data.is_some() {
             let component = &self.component;
-            let component_getters = self.fields.quote_component_getters();
+            let component_getters = self.fields.quote_component_getters(&self.vis);
             let component_setters = self.fields.quote_component_setters();
             quote! {
                 impl #component {
@@ -163,16 +221,23 @@ impl ComponentData {
         let create_fn = self.quote_create_fn();
         let update_fn = self.quote_update_fn();
         let eq_props_fn = self.quote_eq_props_fn();
+        let save_state_fn = self.quote_save_state_fn();
+
+        let required_props = self.fields.quote_required_props();
 
         quote! {
             impl ::papito_dom::Component for #component {
                 type Props = #props_ty;
 
+                #required_props
+
                 #create_fn
 
                 #update_fn
 
                 #eq_props_fn
+
+                #save_state_fn
             }
         }
     }
@@ -181,15 +246,18 @@ impl ComponentData {
         let component = &self.component;
         if let Some(ref data) = self.data {
             let data_init = self.fields.quote_data_init();
+            let restore_state = self.quote_restore_state();
             if self.props.is_some() {
                 quote! {
                     fn create(props: Self::Props, notifier: Box<Fn()>) -> Self {
                         let _data = #data {
-                            #data_init
+                            #data_init,
+                            _computed: ::papito_dom::ComputedCache::new()
                         };
+                        #restore_state
                         #component {
                             _data: ::std::rc::Rc::new(::std::cell::RefCell::new(_data)),
-                            _notifier: notifier
+                            _notifier: ::std::rc::Rc::from(notifier)
                         }
                     }
                 }
@@ -197,11 +265,13 @@ impl ComponentData {
                 quote! {
                     fn create(_: Self::Props, notifier: Box<Fn()>) -> Self {
                         let _data = #data {
-                            #data_init
+                            #data_init,
+                            _computed: ::papito_dom::ComputedCache::new()
                         };
+                        #restore_state
                         #component {
                             _data: ::std::rc::Rc::new(::std::cell::RefCell::new(_data)),
-                            _notifier: notifier
+                            _notifier: ::std::rc::Rc::from(notifier)
                         }
                     }
                 }
@@ -215,13 +285,46 @@ impl ComponentData {
         }
     }
 
+    /// Replaces the initial states with the ones saved by the server, if any.
+    fn quote_restore_state(&self) -> Tokens {
+        let states = self.fields.states();
+        if !self.save_state || states.is_empty() {
+            return quote!();
+        }
+        let data = self.data.as_ref().unwrap();
+        let types: Vec<_> = states.iter().map(|it| &it.ty).collect();
+        let idents: Vec<_> = states.iter().map(|it| &it.ident).collect();
+        let fields = idents.clone();
+        quote! {
+            let _data = match ::papito_dom::restore_state::<( #(#types,)* )>() {
+                Some(( #(#idents,)* )) => #data { #(#fields,)* .._data },
+                None => _data
+            };
+        }
+    }
+
+    fn quote_save_state_fn(&self) -> Tokens {
+        let states = self.fields.states();
+        if !self.save_state || states.is_empty() {
+            return quote!();
+        }
+        let idents: Vec<_> = states.iter().map(|it| &it.ident).collect();
+        quote! {
+            fn save_state(&self) -> Option<String> {
+                let _data = &*self._data.borrow();
+                ::papito_dom::save_state(&( #(&_data.#idents,)* ))
+            }
+        }
+    }
+
     fn quote_update_fn(&self) -> Tokens {
         if self.data.is_some() && self.props.is_some() {
             let props_update = self.fields.quote_props_update();
+            let props_watch = self.fields.quote_props_watch();
             quote! {
                     fn update(&self, props: Self::Props) {
-                        let _data = &mut self._data.borrow_mut();
                         #props_update
+                        #props_watch
                         self._notify();
                     }
                 }
@@ -257,23 +360,30 @@ struct DataFields {
 }
 
 impl DataFields {
-    fn parse(fields: &Fields) -> DataFields {
+    fn parse(fields: &Fields) -> Result<DataFields> {
         match *fields {
-            Fields::Unit => DataFields { fields: vec![] },
-            Fields::Unnamed(_) => {
-                panic!("Tuple structs are not allowed as a `#[component]`")
+            Fields::Unit => Ok(DataFields { fields: vec![] }),
+            Fields::Unnamed(ref unnamed_fields) => {
+                Err(Error::spanned(unnamed_fields, "tuple structs are not allowed as a `#[component]`"))
             }
             Fields::Named(ref named_fields) => {
                 let fields = named_fields.named.iter()
                     .map(|field| DataField::parse(field))
-                    .collect();
-                DataFields {
+                    .collect::<Result<_>>()?;
+                Ok(DataFields {
                     fields
-                }
+                })
             }
         }
     }
 
+    /// The fields which are neither props nor events.
+    fn states(&self) -> Vec<&DataField> {
+        self.fields.iter()
+            .filter(|it| !it.is_prop && !it.is_emit)
+            .collect()
+    }
+
     fn quote_data_fields(&self) -> Option<Tokens> {
         let fields: Vec<_> = self.fields.iter()
             .map(|it| it.quote_data_field())
@@ -287,6 +397,32 @@ impl DataFields {
         }
     }
 
+    fn quote_required_props(&self) -> Tokens {
+        let names: Vec<_> = self.fields.iter()
+            .filter(|it| it.is_required_prop())
+            .map(|it| it.ident.as_ref())
+            .collect();
+        if names.is_empty() {
+            return quote!();
+        }
+        quote! {
+            const REQUIRED_PROPS: &'static [&'static str] = &[ #(#names),* ];
+        }
+    }
+
+    fn quote_props_default(&self) -> Tokens {
+        let defaults: Vec<_> = self.fields.iter()
+            .filter(|it| it.is_prop)
+            .map(|it| {
+                let ident = &it.ident;
+                quote!( #ident: ::std::default::Default::default() )
+            })
+            .collect();
+        quote! {
+            #(#defaults),*
+        }
+    }
+
     fn quote_props_fields(&self) -> Option<Tokens> {
         let fields: Vec<_> = self.fields.iter()
             .map(|it| it.quote_props_field())
@@ -311,9 +447,9 @@ impl DataFields {
         }
     }
 
-    fn quote_component_getters(&self) -> Tokens {
+    fn quote_component_getters(&self, vis: &Visibility) -> Tokens {
         let getters: Vec<_> = self.fields.iter()
-            .map(|it| it.quote_component_getters())
+            .map(|it| it.quote_component_getters(vis))
             .collect();
         quote! {
             #(#getters)*
@@ -356,6 +492,24 @@ impl DataFields {
         }
     }
 
+    fn quote_props_watch(&self) -> Tokens {
+        let watches: Vec<_> = self.fields.iter()
+            .map(|it| it.quote_props_watch())
+            .collect();
+        quote! {
+            #(#watches)*
+        }
+    }
+
+    fn quote_watch_fns(&self) -> Tokens {
+        let watch_fns: Vec<_> = self.fields.iter()
+            .map(|it| it.quote_watch_fn())
+            .collect();
+        quote! {
+            #(#watch_fns)*
+        }
+    }
+
     fn quote_props_eq(&self) -> Tokens {
         let eqs: Vec<_> = self.fields.iter()
             .map(|it| it.quote_props_eq())
@@ -363,7 +517,7 @@ impl DataFields {
             .map(|it| it.unwrap())
             .collect();
         quote! {
-            #(#eqs) && *
+            #((#eqs)) && *
         }
     }
 }
@@ -372,18 +526,66 @@ struct DataField {
     ident: Ident,
     ty: Type,
     is_prop: bool,
+    is_emit: bool,
+    /// A `#[prop(optional)]` which defaults to `Default::default()`
+    is_optional: bool,
+    /// The `default` of a prop or the `init` of a state
+    default: Option<Tokens>,
 }
 
 impl DataField {
-    fn parse(field: &Field) -> DataField {
+    fn parse(field: &Field) -> Result<DataField> {
         if !field.vis.is_private() {
-            panic!("Only private fields allowed.");
+            return Err(Error::spanned(&field.vis, "only private fields are allowed in a `#[component]`"));
         }
-        DataField {
-            ident: field.ident.as_ref().unwrap().clone(),
+        let ident = field.ident.as_ref().unwrap().clone();
+        let mut data_field = DataField {
+            ident,
             ty: field.ty.clone(),
-            is_prop: field.attrs.has_prop_attribute(),
+            is_prop: false,
+            is_emit: false,
+            is_optional: false,
+            default: None,
+        };
+        let mut field_attr_found = false;
+        for attr in field.attrs.iter() {
+            let attr_name = match ["prop", "state", "emit"].iter().find(|name| attr.is_named(name)) {
+                Some(name) => *name,
+                None => continue
+            };
+            if field_attr_found {
+                return Err(Error::spanned(attr, format!(
+                    "the field `{}` can only have one of `#[prop]`, `#[state]` or `#[emit]`", ident)));
+            }
+            field_attr_found = true;
+            let argument = parse_argument(attr, attr_name)?;
+            match (attr_name, argument) {
+                ("prop", None) => data_field.is_prop = true,
+                ("prop", Some(ref arg)) if arg.is("optional", false) => {
+                    data_field.is_prop = true;
+                    data_field.is_optional = true;
+                }
+                ("prop", Some(ref arg)) if arg.is("default", true) => {
+                    data_field.is_prop = true;
+                    data_field.default = arg.value.clone();
+                }
+                ("prop", Some(arg)) => {
+                    return Err(Error::new(arg.span, "`#[prop]` only supports `optional` or `default = expr`"));
+                }
+                ("state", None) => {}
+                ("state", Some(ref arg)) if arg.is("init", true) => {
+                    data_field.default = arg.value.clone();
+                }
+                ("state", Some(arg)) => {
+                    return Err(Error::new(arg.span, "`#[state]` only supports `init = expr`"));
+                }
+                (_, None) => data_field.is_emit = true,
+                (_, Some(_)) => {
+                    return Err(Error::spanned(attr, "`#[emit]` does not take any arguments"));
+                }
+            }
         }
+        Ok(data_field)
     }
 
     fn quote_data_field(&self) -> Tokens {
@@ -394,12 +596,21 @@ impl DataField {
         }
     }
 
+    /// A prop without a `default` which is not `optional`.
+    fn is_required_prop(&self) -> bool {
+        self.is_prop && !self.is_optional && self.default.is_none()
+    }
+
     fn quote_props_field(&self) -> Option<Tokens> {
-        if self.is_prop {
-            let ident = &self.ident;
-            let ty = &self.ty;
+        let ident = &self.ident;
+        let ty = &self.ty;
+        if self.is_required_prop() {
+            Some(quote! {
+                #ident: ::papito_dom::Required<#ty>
+            })
+        } else if self.is_prop {
             Some(quote! {
-                #ident: #ty
+                #ident: ::std::option::Option<#ty>
             })
         } else {
             None
@@ -416,28 +627,36 @@ impl DataField {
         }
     }
 
-    fn quote_component_getters(&self) -> Tokens {
+    fn quote_component_getters(&self, vis: &Visibility) -> Tokens {
         let ident = &self.ident;
         let ty = &self.ty;
-        quote! {
-            fn #ident(&self) -> #ty {
-                self._data.borrow().#ident()
+        if self.is_emit {
+            // Called by the parent to bind its listener
+            quote! {
+                #vis fn #ident(&self) -> #ty {
+                    self._data.borrow().#ident()
+                }
+            }
+        } else {
+            quote! {
+                fn #ident(&self) -> #ty {
+                    self._data.borrow().#ident()
+                }
             }
         }
     }
 
     fn quote_setters(&self) -> Option<Tokens> {
-        if !self.is_prop {
+        if !self.is_prop && !self.is_emit {
             let ident = &self.ident;
             let fn_ident = Ident::from(format!("set_{}", ident));
             let ty = &self.ty;
             Some(quote! {
-                fn #fn_ident(&mut self, value: #ty) -> bool {
+                fn #fn_ident(&mut self, value: #ty) -> Option<#ty> {
                     if self.#ident != value {
-                        self.#ident = value;
-                        true
+                        Some(::std::mem::replace(&mut self.#ident, value))
                     } else {
-                        false
+                        None
                     }
                 }
             })
@@ -447,14 +666,18 @@ impl DataField {
     }
 
     fn quote_component_setters(&self) -> Option<Tokens> {
-        if !self.is_prop {
+        if !self.is_prop && !self.is_emit {
             let ident = &self.ident;
             let fn_ident = Ident::from(format!("set_{}", ident));
             let ty = &self.ty;
+            let name = ident.as_ref();
+            let watch_fn = watch_fn_name(ident.as_ref());
             Some(quote! {
                 fn #fn_ident(&self, value: #ty) {
-                    let changed = self._data.borrow_mut().#fn_ident(value);
-                    if changed {
+                    let old = self._data.borrow_mut().#fn_ident(value);
+                    if let Some(old) = old {
+                        self._data.borrow()._computed.invalidate(#name);
+                        self.#watch_fn(old, self.#ident());
                         self._notify();
                     }
                 }
@@ -467,8 +690,13 @@ impl DataField {
     fn quote_data_init(&self) -> Tokens {
         let ident = &self.ident;
         if self.is_prop {
+            let value = self.quote_prop_value();
             quote! {
-                #ident: props.#ident
+                #ident: #value
+            }
+        } else if let Some(ref init) = self.default {
+            quote! {
+                #ident: #init
             }
         } else {
             quote! {
@@ -477,22 +705,90 @@ impl DataField {
         }
     }
 
+    /// The value of the prop from `props`, which falls back to its default when it is not given.
+    fn quote_prop_value(&self) -> Tokens {
+        let ident = &self.ident;
+        if let Some(ref default) = self.default {
+            quote!( props.#ident.unwrap_or_else(|| #default) )
+        } else if self.is_optional {
+            quote!( props.#ident.unwrap_or_default() )
+        } else {
+            quote!( props.#ident.into_inner() )
+        }
+    }
+
     fn quote_props_update(&self) -> Tokens {
         if self.is_prop {
             let ident = &self.ident;
+            let value = self.quote_prop_value();
+            let name = ident.as_ref();
+            let old_ident = Ident::from(format!("_old_{}", ident));
             quote! {
-                _data.#ident = props.#ident;
+                let #old_ident = {
+                    let _data = &mut self._data.borrow_mut();
+                    let value = #value;
+                    if _data.#ident != value {
+                        _data._computed.invalidate(#name);
+                        Some(::std::mem::replace(&mut _data.#ident, value))
+                    } else {
+                        None
+                    }
+                };
             }
         } else {
             quote!()
         }
     }
 
-    fn quote_props_eq(&self) -> Option<Tokens> {
+    /// Calls the watcher of the prop replaced by the update, after the data is no longer borrowed.
+    fn quote_props_watch(&self) -> Tokens {
         if self.is_prop {
             let ident = &self.ident;
+            let old_ident = Ident::from(format!("_old_{}", ident));
+            let watch_fn = watch_fn_name(ident.as_ref());
+            quote! {
+                if let Some(old) = #old_ident {
+                    self.#watch_fn(old, self.#ident());
+                }
+            }
+        } else {
+            quote!()
+        }
+    }
+
+    fn quote_watch_fn(&self) -> Tokens {
+        if !self.is_emit {
+            let ty = &self.ty;
+            let watch_fn = watch_fn_name(self.ident.as_ref());
+            quote! {
+                fn #watch_fn(&self, _old: #ty, _new: #ty) {}
+            }
+        } else {
+            quote!()
+        }
+    }
+
+    fn quote_props_eq(&self) -> Option<Tokens> {
+        let ident = &self.ident;
+        let ty = &self.ty;
+        if self.is_required_prop() {
             Some(quote! {
-                _data.#ident == props.#ident
+                _data.#ident == *props.#ident.get()
+            })
+        } else if self.is_prop {
+            // An optional prop which is not given is equal to its default
+            let default = match self.default {
+                Some(ref default) => quote!(#default),
+                None => quote!(Default::default())
+            };
+            Some(quote! {
+                match props.#ident {
+                    Some(ref value) => _data.#ident == *value,
+                    None => {
+                        let default: #ty = #default;
+                        _data.#ident == default
+                    }
+                }
             })
         } else {
             None
@@ -500,26 +796,88 @@ impl DataField {
     }
 }
 
-trait HasPropAttribute {
-    fn has_prop_attribute(&self) -> bool;
+/// The name of the watcher of the `field`, called with its old and new values.
+pub fn watch_fn_name(field: &str) -> Ident {
+    Ident::from(format!("_watch_{}", field))
+}
+
+/// The name of the trait with the watchers of the fields of the `component`, which do nothing.
+pub fn watchers_trait_name(component: &Ident) -> Ident {
+    Ident::from(format!("_{}Watchers", component))
+}
+
+trait IsNamed {
+    fn is_named(&self, name: &str) -> bool;
+}
+
+impl IsNamed for Attribute {
+    fn is_named(&self, name: &str) -> bool {
+        self.path == Path::from(Ident::from(name))
+    }
+}
+
+/// The argument of a field attribute, such as `optional` or `default = expr` in `#[prop(...)]`.
+struct Argument {
+    name: String,
+    span: Span,
+    value: Option<Tokens>,
 }
 
-impl HasPropAttribute for Vec<Attribute> {
-    fn has_prop_attribute(&self) -> bool {
-        self.iter().any(|it| it.has_prop_attribute())
+impl Argument {
+    fn is(&self, name: &str, has_value: bool) -> bool {
+        self.name == name && self.value.is_some() == has_value
     }
 }
 
-impl HasPropAttribute for Attribute {
-    fn has_prop_attribute(&self) -> bool {
-        if self.path == Path::from(Ident::from(format!("prop"))) {
-            if !self.tts.is_empty() {
-                panic!("No arguments supported");
+/// Parses the single argument of the attribute, if any.
+fn parse_argument(attr: &Attribute, attr_name: &str) -> Result<Option<Argument>> {
+    let mut tts = attr.tts.clone().into_iter();
+    let inner = match tts.next() {
+        None => return Ok(None),
+        Some(TokenTree { kind: TokenNode::Group(Delimiter::Parenthesis, inner), .. }) => inner,
+        Some(_) => return Err(Error::spanned(attr, format!("expected `#[{}(...)]`", attr_name)))
+    };
+    let mut inner = inner.into_iter();
+    let (name, span) = match inner.next() {
+        Some(TokenTree { span, kind: TokenNode::Term(term) }) => (term.as_str().to_string(), span),
+        Some(token) => return Err(Error::new(token.span, "expected the name of the argument")),
+        None => return Err(Error::spanned(attr, "expected an argument"))
+    };
+    let value = match inner.next() {
+        None => None,
+        Some(TokenTree { kind: TokenNode::Op('=', _), .. }) => {
+            let value: Vec<TokenTree> = inner.collect();
+            if value.is_empty() {
+                return Err(Error::new(span, format!("expected a value for `{}`", name)));
             }
-            true
-        } else {
-            false
+            let mut tokens = Tokens::new();
+            tokens.append_all(value);
+            Some(tokens)
         }
+        Some(token) => return Err(Error::new(token.span, "expected `=`"))
+    };
+    Ok(Some(Argument { name, span, value }))
+}
+
+/// Parses the options of `#[component(...)]`, of which `save_state` is the only one.
+fn parse_save_state(metadata: TokenStream) -> Result<bool> {
+    let mut tokens: Vec<TokenTree> = metadata.into_iter().collect();
+    // The options may be passed along with the parentheses
+    let inner = match tokens.first() {
+        Some(&TokenTree { kind: TokenNode::Group(Delimiter::Parenthesis, ref inner), .. }) if tokens.len() == 1 => {
+            Some(inner.clone())
+        }
+        _ => None
+    };
+    if let Some(inner) = inner {
+        tokens = inner.into_iter().collect();
+    }
+    match tokens.first() {
+        None => Ok(false),
+        Some(&TokenTree { kind: TokenNode::Term(ref term), .. }) if term.as_str() == "save_state" && tokens.len() == 1 => {
+            Ok(true)
+        }
+        Some(token) => Err(Error::new(token.span, "`#[component]` only supports `save_state`"))
     }
 }
 
@@ -0,0 +1,94 @@
+use quote::Tokens;
+use proc_macro2::{TokenStream, TokenTree, TokenNode};
+use syn::{ImplItem, ImplItemMethod, FnArg, ReturnType};
+use error::{Error, Result};
+use render::give_data_access;
+
+pub fn quote(item: ImplItem) -> Result<Tokens> {
+    match item {
+        ImplItem::Method(ref method) => quote_computed_method(method),
+        ref item => {
+            Err(Error::spanned(item, "`#[computed]` attribute can only be used with a method of a component"))
+        }
+    }
+}
+
+/// Caches the result of the method within the component, until any of the fields it reads is set.
+fn quote_computed_method(method: &ImplItemMethod) -> Result<Tokens> {
+    check_signature(method)?;
+    let attrs = &method.attrs;
+    let vis = &method.vis;
+    let sig = &method.sig;
+    let name = sig.ident.as_ref();
+    let stmts = &method.block.stmts;
+    let body: TokenStream = quote!( #(#stmts)* ).into();
+    let mut reads = vec![];
+    collect_reads(body.clone(), &mut reads);
+    let body = give_data_access(body)?;
+    Ok(quote! {
+        #(#attrs)*
+        #vis #sig {
+            self._data.borrow()._computed.get_or_compute(#name, &[ #(#reads),* ], || {
+                #body
+            })
+        }
+    })
+}
+
+fn check_signature(method: &ImplItemMethod) -> Result<()> {
+    let sig = &method.sig;
+    let mut args = sig.decl.inputs.iter();
+    match args.next() {
+        Some(&FnArg::SelfRef(ref self_arg)) if self_arg.mutability.is_none() => {}
+        Some(arg) => {
+            return Err(Error::spanned(arg, format!("the computed method `{}` can only take `&self`", sig.ident)));
+        }
+        None => {
+            return Err(Error::spanned(&sig.ident, format!("the computed method `{}` must take `&self`", sig.ident)));
+        }
+    }
+    if let Some(arg) = args.next() {
+        return Err(Error::spanned(arg, format!("the computed method `{}` cannot have any more arguments", sig.ident)));
+    }
+    if sig.decl.output == ReturnType::Default {
+        return Err(Error::spanned(&sig.ident, format!("the computed method `{}` must return a value", sig.ident)));
+    }
+    Ok(())
+}
+
+/// Collects the names of the fields and the methods read as `self.name`, on which the result of
+/// the computed method depends. The fields read within the other methods it calls are not
+/// collected, unless those are computed as well.
+fn collect_reads(tokens: TokenStream, reads: &mut Vec<String>) {
+    let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+    for (i, token) in tokens.iter().enumerate() {
+        if let TokenNode::Group(_, ref inner) = token.kind {
+            collect_reads(inner.clone(), reads);
+        }
+        if let Some(name) = read_name(&tokens[i..]) {
+            if !reads.contains(&name) {
+                reads.push(name);
+            }
+        }
+    }
+}
+
+fn read_name(tokens: &[TokenTree]) -> Option<String> {
+    if tokens.len() < 3 {
+        return None;
+    }
+    let is_self = match tokens[0].kind {
+        TokenNode::Term(ref term) => term.as_str() == "self",
+        _ => false
+    };
+    let is_dot = match tokens[1].kind {
+        TokenNode::Op('.', _) => true,
+        _ => false
+    };
+    match tokens[2].kind {
+        TokenNode::Term(ref term) if is_self && is_dot && !term.as_str().starts_with('_') => {
+            Some(term.as_str().to_string())
+        }
+        _ => None
+    }
+}
@@ -0,0 +1,47 @@
+use proc_macro;
+use proc_macro2::{TokenStream, TokenTree, TokenNode, Term, Span, Delimiter, Literal, Spacing};
+use syn;
+use syn::spanned::Spanned;
+use syn::synom::Synom;
+
+/// An error reported as a `compile_error!` on the offending tokens instead of a panic.
+pub struct Error {
+    span: Span,
+    message: String,
+}
+
+impl Error {
+    pub fn new<T: Into<String>>(span: Span, message: T) -> Error {
+        Error {
+            span,
+            message: message.into(),
+        }
+    }
+
+    /// An error pointing at the syntax tree `node`.
+    pub fn spanned<N: Spanned, T: Into<String>>(node: &N, message: T) -> Error {
+        Error::new(node.span(), message)
+    }
+
+    pub fn to_compile_error(&self) -> TokenStream {
+        let span = self.span;
+        let message: TokenStream = vec![TokenTree {
+            span,
+            kind: TokenNode::Literal(Literal::string(&self.message)),
+        }].into_iter().collect();
+        vec![
+            TokenTree { span, kind: TokenNode::Term(Term::intern("compile_error")) },
+            TokenTree { span, kind: TokenNode::Op('!', Spacing::Alone) },
+            TokenTree { span, kind: TokenNode::Group(Delimiter::Brace, message) },
+        ].into_iter().collect()
+    }
+}
+
+pub type Result<T> = ::std::result::Result<T, Error>;
+
+/// Parses the input of a macro. The parser does not tell where it failed, so the error is reported
+/// on the macro call.
+pub fn parse<T: Synom>(input: proc_macro::TokenStream) -> Result<T> {
+    syn::parse(input)
+        .map_err(|err| Error::new(Span::call_site(), format!("could not parse the input: {}", err)))
+}
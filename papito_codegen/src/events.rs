@@ -1,95 +1,101 @@
 use quote::Tokens;
-use syn::{Item, Type, Ident, Path, ImplItem, ImplItemMethod, FnArg, ArgSelfRef, ArgCaptured,
+use syn::{Item, ItemImpl, Ident, Path, ImplItem, ImplItemMethod, FnArg, ArgSelfRef, ArgCaptured,
           ReturnType};
-use common::{split_path, component_of_state};
+use error::{Error, Result};
 
-pub fn quote(item: &Item) -> Tokens {
-    match *item {
-        Item::Impl(ref item_impl) => {
-            let self_ty = *item_impl.self_ty.clone();
-            let path = component_path_of(self_ty);
-            impl_wrapper_for_any_events(item_impl.items.clone(), path)
-        }
-        _ => {
-            panic!("`#[events]` attribute can only be used with an impl block");
-        }
-    }
-}
+pub fn quote(item: Item) -> Result<Tokens> {
+    match item {
+        Item::Impl(mut item_impl) => {
+            let event_wrappers = rename_event_handlers(&mut item_impl)?;
+            let self_ty = &item_impl.self_ty;
+            Ok(quote! {
+                #item_impl
 
-fn component_path_of(self_ty: Type) -> Path {
-    match self_ty {
-        Type::Path(type_path) => {
-            let (mut path, mut last_segment) = split_path(type_path);
-            last_segment.ident = component_of_state(&last_segment.ident);
-            path.segments.push(last_segment);
-            path
+                impl #self_ty {
+                    #(#event_wrappers)*
+                }
+            })
+        }
+        ref item => {
+            Err(Error::spanned(item, "`#[events]` attribute can only be used with an impl block"))
         }
-        _ => panic!("Only type paths are allowed for impls attributed with `#[events]`")
     }
 }
 
-fn impl_wrapper_for_any_events(items: Vec<ImplItem>, comp_path: Path) -> Tokens {
-    let mut iter = items.into_iter();
+/// Renames each `#[event]` method to `_event_{name}` and creates a wrapper in its place that
+/// returns the listener to be used within `h!`. The listener holds a handle of the component, so
+/// that the handler may call the `set_*` setters.
+fn rename_event_handlers(item_impl: &mut ItemImpl) -> Result<Vec<Tokens>> {
     let mut event_wrappers = vec![];
-    while let Some(ImplItem::Method(method_item)) = iter.next() {
-        if has_event_attribute(&method_item) {
-            let (fn_name, self_arg, event_arg) = get_metadata(method_item);
+    for item in item_impl.items.iter_mut() {
+        if let ImplItem::Method(ref mut method_item) = *item {
+            if !has_event_attribute(method_item) {
+                continue;
+            }
+            method_item.attrs.retain(|it| !is_event_attribute(it));
+            let (fn_name, self_arg, event_arg) = get_metadata(method_item.clone())?;
+            let handler_name = Ident::from(format!("_event_{}", fn_name));
+            method_item.sig.ident = handler_name;
             let event_ty = &event_arg.ty;
             let event_ident = Ident::from("ev".to_string());
             if self_arg.mutability.is_some() {
                 event_wrappers.push(quote! {
                     fn #fn_name(&self) -> impl FnMut(#event_ty) {
-                        let comp = self.inner.clone();
+                        let mut comp = self._handle();
                         move |#event_ident| {
-                            comp.borrow_mut().#fn_name(#event_ident);
+                            comp.#handler_name(#event_ident);
                         }
                     }
                 })
             } else {
                 event_wrappers.push(quote! {
                     fn #fn_name(&self) -> impl Fn(#event_ty) {
-                        let comp = self.inner.clone();
+                        let comp = self._handle();
                         move |#event_ident| {
-                            comp.borrow().#fn_name(#event_ident);
+                            comp.#handler_name(#event_ident);
                         }
                     }
                 })
             }
         }
     }
-    quote! {
-        impl #comp_path {
-            #(#event_wrappers)*
-        }
-    }
+    Ok(event_wrappers)
 }
 
 fn has_event_attribute(item: &ImplItemMethod) -> bool {
-    item.attrs.iter().any(|it| it.path == Path::from(Ident::from("event".to_string())))
+    item.attrs.iter().any(is_event_attribute)
+}
+
+fn is_event_attribute(attr: &::syn::Attribute) -> bool {
+    attr.path == Path::from(Ident::from("event".to_string()))
 }
 
-fn get_metadata(item: ImplItemMethod) -> (Ident, ArgSelfRef, ArgCaptured) {
+fn get_metadata(item: ImplItemMethod) -> Result<(Ident, ArgSelfRef, ArgCaptured)> {
     let sig = item.sig;
     let fn_name = sig.ident;
     let decl = sig.decl;
     if decl.output != ReturnType::Default {
-        panic!("This event method `{}` cannot have a return type", &fn_name)
+        return Err(Error::spanned(&decl.output, format!("the event method `{}` cannot have a return type", fn_name)));
     }
     let mut args = decl.inputs.into_iter();
-    let first_arg = args.next().expect(&format!("This method `{}` has no argument", &fn_name));
-    let second_arg = args.next().expect(&format!("This method `{}` does not have second argument", &fn_name));
-    if let Some(_) = args.next() {
-        panic!("This method `{}` cannot have any more that 2 arguments", &fn_name);
+    let first_arg = match args.next() {
+        Some(arg) => arg,
+        None => return Err(Error::spanned(&fn_name, format!("the event method `{}` has no arguments", fn_name)))
+    };
+    let second_arg = match args.next() {
+        Some(arg) => arg,
+        None => return Err(Error::spanned(&fn_name, format!("the event method `{}` does not have the event argument", fn_name)))
+    };
+    if let Some(arg) = args.next() {
+        return Err(Error::spanned(&arg, format!("the event method `{}` cannot have more than 2 arguments", fn_name)));
     }
-    let first_arg = if let FnArg::SelfRef(self_arg) = first_arg {
-        self_arg
-    } else {
-        panic!("The first arg of `{}` can only be `&self` or `&mut self`", &fn_name);
+    let first_arg = match first_arg {
+        FnArg::SelfRef(self_arg) => self_arg,
+        arg => return Err(Error::spanned(&arg, format!("the first argument of `{}` can only be `&self` or `&mut self`", fn_name)))
     };
-    let second_arg = if let FnArg::Captured(arg) = second_arg {
-        arg
-    } else {
-        panic!("The second arg of `{}` must be an explicit type", &fn_name);
+    let second_arg = match second_arg {
+        FnArg::Captured(arg) => arg,
+        arg => return Err(Error::spanned(&arg, format!("the second argument of `{}` must have an explicit type", fn_name)))
     };
-    (fn_name, first_arg, second_arg)
-}
\ No newline at end of file
+    Ok((fn_name, first_arg, second_arg))
+}
@@ -1,5 +1,3 @@
-#![feature(proc_macro)]
-
 extern crate proc_macro;
 #[macro_use]
 extern crate quote;
@@ -8,42 +6,98 @@ extern crate heck;
 extern crate proc_macro2;
 
 use proc_macro::TokenStream;
-use syn::{Item, DeriveInput};
+use syn::DeriveInput;
+use proc_macro2::Span;
+use error::{Error, Result};
 
+mod error;
 mod events;
-mod common;
 mod component;
+mod render;
+mod computed;
+mod watchers;
+mod template;
+
+/// With `#[component(save_state)]`, the states are saved on the server and restored on the client
+/// when hydrating. Requires the `save_state` feature of `papito_dom`.
+#[proc_macro_attribute]
+pub fn component(metadata: TokenStream, input: TokenStream) -> TokenStream {
+    to_token_stream(error::parse(input).and_then(|item| component::quote(metadata.into(), item)))
+}
 
+/// Gives the `render` method of an `impl Render for` the component a read access to its data
+/// fields, when borrowed as `&self.field` or read through as `self.field.len()`. A field cannot be
+/// read by value, as it would be moved out of the data.
 #[proc_macro_attribute]
-pub fn component(_metadata: TokenStream, input: TokenStream) -> TokenStream {
-    let item: Item = syn::parse(input).unwrap();
-    component::quote(item).into()
+pub fn render(_metadata: TokenStream, input: TokenStream) -> TokenStream {
+    to_token_stream(error::parse(input).and_then(render::quote))
+}
+
+/// Caches the result of a `fn(&self) -> T` method of a component, which is recomputed only after
+/// any of the fields it reads as `self.field` is set. The fields read within the other methods it
+/// calls are not tracked, unless those methods are computed as well.
+#[proc_macro_attribute]
+pub fn computed(_metadata: TokenStream, input: TokenStream) -> TokenStream {
+    to_token_stream(error::parse(input).and_then(computed::quote))
+}
+
+/// The Vue-like template syntax, which expands to the same vnodes as `h!`.
+#[proc_macro]
+pub fn html(input: TokenStream) -> TokenStream {
+    to_token_stream(template::quote(input.into()))
+}
+
+/// An alias of `html!`.
+#[proc_macro]
+pub fn template(input: TokenStream) -> TokenStream {
+    to_token_stream(template::quote(input.into()))
 }
 
 #[proc_macro_derive(Lifecycle)]
 pub fn derive_lifecycle(input: TokenStream) -> TokenStream {
-    let derive: DeriveInput = syn::parse(input).unwrap();
-    let ident = &derive.ident;
-    let expanded = quote! {
-        impl ::papito::prelude::Lifecycle for #ident {}
-    };
-    expanded.into()
+    let derive = error::parse::<DeriveInput>(input).map(|derive| {
+        let ident = &derive.ident;
+        quote! {
+            impl ::papito::prelude::Lifecycle for #ident {}
+        }
+    });
+    to_token_stream(derive)
 }
 
 #[proc_macro_attribute]
 pub fn events(_metadata: TokenStream, input: TokenStream) -> TokenStream {
-    let state: Item = syn::parse(input).unwrap();
-    let wrapper = events::quote(&state);
-    let expanded = quote! {
-        #state
-
-        #wrapper
-    };
-    expanded.into()
+    to_token_stream(error::parse(input).and_then(events::quote))
 }
 
-// Just a placeholder attribute to be used by `#[events]` otherwise it does not compile
+// Just a placeholder attribute to be used by `#[events]`, which strips it from the handlers
 #[proc_macro_attribute]
 pub fn event(_metadata: TokenStream, input: TokenStream) -> TokenStream {
     input
-}
\ No newline at end of file
+}
+
+/// Calls the `#[watch(field)]` methods within the impl block with the old and new values, whenever
+/// the field is set or the prop is updated. The impl block is of a `#[component]` and each field
+/// can be watched by only one method.
+#[proc_macro_attribute]
+pub fn watchers(_metadata: TokenStream, input: TokenStream) -> TokenStream {
+    to_token_stream(error::parse(input).and_then(watchers::quote))
+}
+
+/// Marks a method as the watcher of a field. It only works within an impl block marked with
+/// `#[watchers]`, which strips it from the watchers, so it is an error whenever it is expanded.
+#[proc_macro_attribute]
+pub fn watch(_metadata: TokenStream, _input: TokenStream) -> TokenStream {
+    let err = Error::new(Span::call_site(), "`#[watch(field)]` can only be used within an impl block marked with `#[watchers]`");
+    to_token_stream::<proc_macro2::TokenStream>(Err(err))
+}
+
+/// Reports the error, if any, as a `compile_error!` on the offending tokens.
+fn to_token_stream<T: Into<proc_macro2::TokenStream>>(result: Result<T>) -> TokenStream {
+    match result {
+        Ok(tokens) => {
+            let tokens: proc_macro2::TokenStream = tokens.into();
+            tokens.into()
+        }
+        Err(err) => err.to_compile_error().into()
+    }
+}
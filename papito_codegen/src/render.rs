@@ -0,0 +1,162 @@
+use proc_macro2::{TokenStream, TokenTree, TokenNode, Term, Span, Delimiter, Spacing};
+use syn::{Item, ItemImpl, ImplItem};
+use error::{Error, Result};
+
+pub fn quote(item: Item) -> Result<TokenStream> {
+    match item {
+        Item::Impl(ref item_impl) => {
+            check_is_render_impl(item_impl)?;
+            let tokens: TokenStream = quote!(#item_impl).into();
+            rewrite_render_fn(tokens)
+        }
+        ref item => {
+            Err(Error::spanned(item, "`#[render]` attribute can only be used with an impl block"))
+        }
+    }
+}
+
+fn check_is_render_impl(item_impl: &ItemImpl) -> Result<()> {
+    let is_render_trait = item_impl.trait_.as_ref()
+        .and_then(|&(_, ref path, _)| path.segments.iter().last())
+        .map(|segment| segment.ident.as_ref() == "Render")
+        .unwrap_or(false);
+    if !is_render_trait {
+        return Err(Error::spanned(&item_impl.self_ty, "`#[render]` can only be used on an `impl Render for` the component"));
+    }
+    let has_render_fn = item_impl.items.iter().any(|it| match *it {
+        ImplItem::Method(ref method) => method.sig.ident.as_ref() == "render",
+        _ => false
+    });
+    if !has_render_fn {
+        return Err(Error::spanned(&item_impl.self_ty, "`#[render]` requires the `render` method to be implemented"));
+    }
+    Ok(())
+}
+
+/// Finds the body of the `render` method and gives it a read access to the data fields of the
+/// component, so that `&self.click` can be used instead of `&self._data.borrow().click`.
+fn rewrite_render_fn(tokens: TokenStream) -> Result<TokenStream> {
+    let mut is_render_fn = false;
+    let mut rewritten = vec![];
+    for token in tokens.into_iter() {
+        let kind = match token.kind {
+            TokenNode::Term(ref term) if term.as_str() == "render" => {
+                is_render_fn = true;
+                token.kind.clone()
+            }
+            TokenNode::Group(Delimiter::Brace, ref body) if is_render_fn => {
+                is_render_fn = false;
+                TokenNode::Group(Delimiter::Brace, give_data_access(body.clone())?)
+            }
+            TokenNode::Group(delimiter, ref inner) if !is_render_fn => {
+                TokenNode::Group(delimiter, rewrite_render_fn(inner.clone())?)
+            }
+            _ => token.kind.clone()
+        };
+        rewritten.push(TokenTree { span: token.span, kind });
+    }
+    Ok(rewritten.into_iter().collect())
+}
+
+/// Gives the body a read access to the data fields of the component, also used by `#[computed]`.
+pub fn give_data_access(body: TokenStream) -> Result<TokenStream> {
+    let mut has_data_access = false;
+    let body = rewrite_data_access(body, &mut has_data_access)?;
+    if has_data_access {
+        let data_binding: TokenStream = quote!( let _data = self._data.borrow(); ).into();
+        Ok(data_binding.into_iter().chain(body.into_iter()).collect())
+    } else {
+        Ok(body)
+    }
+}
+
+/// Rewrites `self.field` into `_data.field`, within the macros as well, when the field is borrowed
+/// or read through, e.g. `&self.field`, `self.field.len()` or `self.field[0]`. Reading the field
+/// by value is an error, as it cannot be moved out of the data. The method calls, the fields
+/// generated by `#[component]` starting with an `_`, and the bodies of the nested items, whose
+/// `self` is not the component, are left as is.
+fn rewrite_data_access(tokens: TokenStream, has_data_access: &mut bool) -> Result<TokenStream> {
+    let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+    let mut rewritten = vec![];
+    let mut is_nested_item = false;
+    for (i, token) in tokens.iter().enumerate() {
+        if let Some((name, span)) = data_field(&tokens[i..]) {
+            let is_borrowed = i > 0 && is_op(&tokens[i - 1], '&');
+            if !is_borrowed && !is_read_through(&tokens[i + 3..]) {
+                return Err(Error::new(span, format!(
+                    "`self.{0}` cannot be moved out of the data, borrow it as `&self.{0}` or clone it as `self.{0}.clone()`",
+                    name
+                )));
+            }
+            *has_data_access = true;
+            rewritten.push(TokenTree {
+                span: Span::call_site(),
+                kind: TokenNode::Term(Term::intern("_data")),
+            });
+            continue;
+        }
+        let kind = match token.kind {
+            TokenNode::Term(ref term) if ["fn", "impl", "trait"].contains(&term.as_str()) => {
+                is_nested_item = true;
+                token.kind.clone()
+            }
+            // A `fn` pointer type
+            TokenNode::Op(';', _) => {
+                is_nested_item = false;
+                token.kind.clone()
+            }
+            TokenNode::Group(Delimiter::Brace, _) if is_nested_item => {
+                is_nested_item = false;
+                token.kind.clone()
+            }
+            TokenNode::Group(delimiter, ref inner) => {
+                TokenNode::Group(delimiter, rewrite_data_access(inner.clone(), has_data_access)?)
+            }
+            ref kind => kind.clone()
+        };
+        rewritten.push(TokenTree { span: token.span, kind });
+    }
+    Ok(rewritten.into_iter().collect())
+}
+
+/// The name and the span of the field of a `self.field` access, which is not a method call.
+fn data_field(tokens: &[TokenTree]) -> Option<(String, Span)> {
+    if tokens.len() < 3 {
+        return None;
+    }
+    let is_self = match tokens[0].kind {
+        TokenNode::Term(ref term) => term.as_str() == "self",
+        _ => false
+    };
+    let field = match tokens[2].kind {
+        TokenNode::Term(ref term) if !term.as_str().starts_with('_') => Some(term.as_str().to_string()),
+        _ => None
+    };
+    let is_method_call = match tokens.get(3).map(|it| &it.kind) {
+        Some(&TokenNode::Group(Delimiter::Parenthesis, _)) => true,
+        // A turbofish
+        Some(&TokenNode::Op(':', _)) => true,
+        _ => false
+    };
+    if is_self && is_op(&tokens[1], '.') && !is_method_call {
+        field.map(|it| (it, tokens[2].span))
+    } else {
+        None
+    }
+}
+
+/// Whether the field is followed by a `.` or an index, rather than a range.
+fn is_read_through(rest: &[TokenTree]) -> bool {
+    match rest.first().map(|it| &it.kind) {
+        Some(&TokenNode::Op('.', Spacing::Alone)) => true,
+        Some(&TokenNode::Group(Delimiter::Bracket, _)) => true,
+        _ => false
+    }
+}
+
+fn is_op(token: &TokenTree, op: char) -> bool {
+    match token.kind {
+        TokenNode::Op(it, _) => it == op,
+        _ => false
+    }
+}
@@ -0,0 +1,469 @@
+use quote::Tokens;
+use syn::Ident;
+use proc_macro2::{TokenStream, TokenTree, TokenNode, Span, Delimiter, Literal};
+use error::{Error, Result};
+
+/// Parses the html-like template into the same `papito_dom` calls that `h!` expands to, through
+/// their re-exports in `papito`.
+///
+/// * `<div class="text" id={expr}>...</div>` and `<input disabled />` create elements.
+/// * `<Button style={expr} @on_toggle={listener} />` creates a component, while
+///   `<Button style={expr} .. />` leaves the rest of its props to their defaults, when all of
+///   the required ones are given.
+/// * `@click={listener}` binds a DOM event on an element.
+/// * `"text"` creates a text node and `{expr}` interpolates a string or any vnode.
+/// * `if cond { ... } else { ... }` and `for pat in iter { ... }` are the directives.
+pub fn quote(input: TokenStream) -> Result<Tokens> {
+    let mut parser = Parser::new(input, Span::call_site());
+    let nodes = parser.parse_nodes()?;
+    if let Some(token) = parser.peek() {
+        return Err(Error::new(token.span, "unexpected closing tag"));
+    }
+    match nodes.len() {
+        0 => Err(Error::new(Span::call_site(), "the template cannot be empty")),
+        1 => Ok(nodes.into_iter().next().unwrap()),
+        _ => Ok(quote_list(nodes))
+    }
+}
+
+/// The name of a tag or an attribute along with the tokens it was parsed from.
+struct Name {
+    name: String,
+    span: Span,
+    tokens: Vec<TokenTree>,
+}
+
+impl Name {
+    fn is_component(&self) -> bool {
+        self.name.chars().next().map(|it| it.is_uppercase()).unwrap_or(false)
+    }
+
+    fn as_path(&self) -> TokenStream {
+        self.tokens.iter().cloned().collect()
+    }
+}
+
+struct Attribute {
+    name: Name,
+    value: Tokens,
+}
+
+struct Parser {
+    tokens: Vec<TokenTree>,
+    pos: usize,
+    /// The span of the group the tokens are within.
+    span: Span,
+}
+
+impl Parser {
+    fn new(input: TokenStream, span: Span) -> Parser {
+        Parser {
+            tokens: input.into_iter().collect(),
+            pos: 0,
+            span,
+        }
+    }
+
+    fn peek(&self) -> Option<&TokenTree> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_at(&self, n: usize) -> Option<&TokenTree> {
+        self.tokens.get(self.pos + n)
+    }
+
+    fn next(&mut self) -> Option<TokenTree> {
+        let token = self.tokens.get(self.pos).cloned();
+        if token.is_some() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    /// The span of the last token read, or else of the group, to point at where more was expected.
+    fn cursor_span(&self) -> Span {
+        self.pos.checked_sub(1)
+            .and_then(|it| self.tokens.get(it))
+            .map(|it| it.span)
+            .unwrap_or(self.span)
+    }
+
+    fn peek_is_op(&self, n: usize, op: char) -> bool {
+        self.peek_at(n).map(|it| is_op(it, op)).unwrap_or(false)
+    }
+
+    fn expect_op(&mut self, op: char) -> Result<()> {
+        match self.next() {
+            Some(ref token) if is_op(token, op) => Ok(()),
+            Some(token) => Err(Error::new(token.span, format!("expected `{}`", op))),
+            None => Err(Error::new(self.cursor_span(), format!("expected `{}`", op)))
+        }
+    }
+
+    /// Parses the nodes till the end or a closing tag.
+    fn parse_nodes(&mut self) -> Result<Vec<Tokens>> {
+        let mut nodes = vec![];
+        while self.peek().is_some() && !(self.peek_is_op(0, '<') && self.peek_is_op(1, '/')) {
+            nodes.push(self.parse_node()?);
+        }
+        Ok(nodes)
+    }
+
+    fn parse_node(&mut self) -> Result<Tokens> {
+        let token = self.next().expect("Impossible. Only called when there is a token");
+        match token.kind {
+            TokenNode::Op('<', _) => self.parse_element(),
+            TokenNode::Literal(ref text) => Ok(quote!(::papito::__codegen::h(::papito::__codegen::txt(#text)))),
+            TokenNode::Group(Delimiter::Brace, ref expr) => Ok(quote!(::papito::__codegen::interpolate(#expr))),
+            TokenNode::Term(ref term) if term.as_str() == "if" => self.parse_if(),
+            TokenNode::Term(ref term) if term.as_str() == "for" => self.parse_for(),
+            _ => Err(Error::new(token.span, "expected an element, a text literal, `{expr}`, `if` or `for`"))
+        }
+    }
+
+    /// Parses a `-` separated name such as `data-id`, or a `::` separated component path.
+    fn parse_name(&mut self) -> Result<Name> {
+        let first = match self.next() {
+            Some(token) => token,
+            None => return Err(Error::new(self.cursor_span(), "expected a name"))
+        };
+        let mut name = match first.kind {
+            TokenNode::Term(ref term) => term.as_str().to_string(),
+            _ => return Err(Error::new(first.span, "expected a name"))
+        };
+        let span = first.span;
+        let mut tokens = vec![first];
+        loop {
+            let separator = if self.peek_is_op(0, '-') {
+                1
+            } else if self.peek_is_op(0, ':') && self.peek_is_op(1, ':') {
+                2
+            } else {
+                break;
+            };
+            let term = match self.peek_at(separator).map(|it| &it.kind) {
+                Some(&TokenNode::Term(ref term)) => term.as_str().to_string(),
+                _ => break
+            };
+            for _ in 0..(separator + 1) {
+                tokens.push(self.next().unwrap());
+            }
+            name.push_str(if separator == 1 { "-" } else { "::" });
+            name.push_str(&term);
+        }
+        Ok(Name { name, span, tokens })
+    }
+
+    /// Parses the value after `=`, either a literal or an `{expr}`.
+    fn parse_value(&mut self) -> Result<Tokens> {
+        match self.next() {
+            Some(token) => match token.kind {
+                TokenNode::Literal(ref value) => Ok(quote!(#value)),
+                TokenNode::Group(Delimiter::Brace, ref expr) => Ok(quote!((#expr))),
+                _ => Err(Error::new(token.span, "expected a literal or `{expr}`"))
+            },
+            None => Err(Error::new(self.cursor_span(), "expected a literal or `{expr}`"))
+        }
+    }
+
+    fn parse_element(&mut self) -> Result<Tokens> {
+        let tag = self.parse_name()?;
+        let mut attrs = vec![];
+        let mut events = vec![];
+        let mut is_partial = false;
+        let is_self_closing = loop {
+            let token = match self.peek() {
+                Some(token) => token.clone(),
+                None => return Err(Error::new(self.cursor_span(), format!("the element `<{}>` is not closed", tag.name)))
+            };
+            match token.kind {
+                TokenNode::Op('/', _) => {
+                    self.next();
+                    self.expect_op('>')?;
+                    break true;
+                }
+                TokenNode::Op('>', _) => {
+                    self.next();
+                    break false;
+                }
+                // The rest of the props are their defaults, as in `<Button label={label} .. />`
+                TokenNode::Op('.', _) if self.peek_is_op(1, '.') => {
+                    self.next();
+                    self.next();
+                    if !tag.is_component() {
+                        return Err(Error::new(token.span, "only a component can be given a few of its props with `..`"));
+                    }
+                    is_partial = true;
+                }
+                TokenNode::Op('@', _) => {
+                    self.next();
+                    let name = self.parse_name()?;
+                    self.expect_op('=')?;
+                    let value = self.parse_value()?;
+                    events.push(Attribute { name, value });
+                }
+                TokenNode::Term(_) => {
+                    let name = self.parse_name()?;
+                    let value = if self.peek_is_op(0, '=') {
+                        self.next();
+                        self.parse_value()?
+                    } else {
+                        // A boolean attribute
+                        quote!(true)
+                    };
+                    attrs.push(Attribute { name, value });
+                }
+                _ => return Err(Error::new(token.span, "expected an attribute, `>` or `/>`"))
+            }
+        };
+        let children = if is_self_closing {
+            vec![]
+        } else {
+            let children = self.parse_nodes()?;
+            if self.peek().is_none() {
+                return Err(Error::new(self.cursor_span(), format!("the element `<{}>` is not closed", tag.name)));
+            }
+            self.expect_op('<')?;
+            self.expect_op('/')?;
+            let closing = self.parse_name()?;
+            if closing.name != tag.name {
+                return Err(Error::new(closing.span, format!("expected `</{}>`", tag.name)));
+            }
+            self.expect_op('>')?;
+            children
+        };
+        if tag.is_component() {
+            if !children.is_empty() {
+                return Err(Error::new(tag.span, format!("the component `{}` cannot have any children", tag.name)));
+            }
+            quote_component(tag, attrs, events, is_partial)
+        } else {
+            quote_element(tag, attrs, events, children)
+        }
+    }
+
+    /// Parses the tokens till the body of a directive.
+    fn parse_till_body(&mut self, stop_at_in: bool) -> Result<(TokenStream, Vec<Tokens>)> {
+        let mut head = vec![];
+        loop {
+            let token = match self.next() {
+                Some(token) => token,
+                None => return Err(Error::new(self.cursor_span(), "expected the `{ ... }` body"))
+            };
+            match token.kind {
+                TokenNode::Group(Delimiter::Brace, ref body) if !stop_at_in => {
+                    if head.is_empty() {
+                        return Err(Error::new(token.span, "expected an expression before the body"));
+                    }
+                    let mut parser = Parser::new(body.clone(), token.span);
+                    let nodes = parser.parse_nodes()?;
+                    if let Some(token) = parser.peek() {
+                        return Err(Error::new(token.span, "unexpected closing tag"));
+                    }
+                    return Ok((head.into_iter().collect(), nodes));
+                }
+                TokenNode::Term(ref term) if stop_at_in && term.as_str() == "in" => {
+                    if head.is_empty() {
+                        return Err(Error::new(token.span, "expected a pattern before `in`"));
+                    }
+                    return Ok((head.into_iter().collect(), vec![]));
+                }
+                _ => head.push(token.clone())
+            }
+        }
+    }
+
+    fn parse_if(&mut self) -> Result<Tokens> {
+        let (cond, body) = self.parse_till_body(false)?;
+        let body = quote_nodes(body);
+        let is_else = match self.peek().map(|it| &it.kind) {
+            Some(&TokenNode::Term(ref term)) => term.as_str() == "else",
+            _ => false
+        };
+        let else_body = if is_else {
+            self.next();
+            let is_else_if = match self.peek().map(|it| &it.kind) {
+                Some(&TokenNode::Term(ref term)) => term.as_str() == "if",
+                _ => false
+            };
+            if is_else_if {
+                self.next();
+                self.parse_if()?
+            } else {
+                match self.next() {
+                    Some(TokenTree { kind: TokenNode::Group(Delimiter::Brace, body), span }) => {
+                        let mut parser = Parser::new(body, span);
+                        let nodes = parser.parse_nodes()?;
+                        if let Some(token) = parser.peek() {
+                            return Err(Error::new(token.span, "unexpected closing tag"));
+                        }
+                        quote_nodes(nodes)
+                    }
+                    Some(token) => return Err(Error::new(token.span, "expected the `{ ... }` body of `else`")),
+                    None => return Err(Error::new(self.cursor_span(), "expected the `{ ... }` body of `else`"))
+                }
+            }
+        } else {
+            quote_nodes(vec![])
+        };
+        Ok(quote! {
+            if #cond {
+                #body
+            } else {
+                #else_body
+            }
+        })
+    }
+
+    fn parse_for(&mut self) -> Result<Tokens> {
+        let (pat, _) = self.parse_till_body(true)?;
+        let (iter, body) = self.parse_till_body(false)?;
+        let body = quote_nodes(body);
+        Ok(quote! {
+            ::papito::__codegen::h(::papito::__codegen::li(
+                (#iter).into_iter()
+                    .map(|#pat| #body)
+                    .collect::<::std::vec::Vec<_>>()
+            ))
+        })
+    }
+}
+
+fn is_op(token: &TokenTree, op: char) -> bool {
+    match token.kind {
+        TokenNode::Op(it, _) => it == op,
+        _ => false
+    }
+}
+
+fn quote_list(nodes: Vec<Tokens>) -> Tokens {
+    quote!(::papito::__codegen::h(::papito::__codegen::li(vec![ #(#nodes),* ])))
+}
+
+/// A single node is used as is, otherwise they are grouped into a list.
+fn quote_nodes(nodes: Vec<Tokens>) -> Tokens {
+    if nodes.len() == 1 {
+        nodes.into_iter().next().unwrap()
+    } else if nodes.is_empty() {
+        quote!(::papito::__codegen::h(::papito::__codegen::li(::std::vec::Vec::<::papito::__codegen::prelude::VNode>::new())))
+    } else {
+        quote_list(nodes)
+    }
+}
+
+fn quote_element(tag: Name, attrs: Vec<Attribute>, events: Vec<Attribute>, children: Vec<Tokens>) -> Result<Tokens> {
+    let tag_name = Literal::string(&tag.name);
+    let attrs: Vec<_> = attrs.into_iter()
+        .map(|Attribute { name, value }| {
+            let name = Literal::string(&name.name);
+            quote!( (#name, ::papito::__codegen::attr(#value)) )
+        })
+        .collect();
+    let el = match (attrs.is_empty(), children.is_empty()) {
+        (true, true) => quote!(::papito::__codegen::el((#tag_name, ()))),
+        (true, false) => quote!(::papito::__codegen::el((#tag_name, vec![ #(#children),* ]))),
+        (false, true) => quote!(::papito::__codegen::el((#tag_name, vec![ #(#attrs),* ]))),
+        (false, false) => quote!(::papito::__codegen::el((#tag_name, vec![ #(#attrs),* ], vec![ #(#children),* ]))),
+    };
+    if events.is_empty() {
+        return Ok(quote!(::papito::__codegen::h(#el)));
+    }
+    let mut listeners = vec![];
+    for Attribute { name, value } in events {
+        let event_ty = match event_type_of(&name.name) {
+            Some(event_ty) => Ident::from(event_ty),
+            None => return Err(Error::new(name.span, format!("unknown event `@{}`", name.name)))
+        };
+        listeners.push(quote!(::papito::__codegen::ev::<_, ::papito::__codegen::event::#event_ty, _>(#value)));
+    }
+    Ok(quote!({
+        #[allow(unused_mut)]
+        let mut el = #el;
+        #[cfg(target_arch = "wasm32")]
+        el.set_events(vec![ #(#listeners),* ]);
+        ::papito::__codegen::h(el)
+    }))
+}
+
+fn quote_component(tag: Name, props: Vec<Attribute>, events: Vec<Attribute>, is_partial: bool) -> Result<Tokens> {
+    let ty = tag.as_path();
+    let mut fields = vec![];
+    let mut names = vec![];
+    for Attribute { name, value } in props {
+        if name.tokens.len() != 1 {
+            return Err(Error::new(name.span, format!("`{}` is not a valid prop name", name.name)));
+        }
+        let field = name.as_path();
+        fields.push(quote!(#field: ::papito::__codegen::prop(#value)));
+        names.push(name.name);
+    }
+    let props = if is_partial {
+        quote!({
+            type T = <#ty as ::papito::__codegen::Component>::Props;
+            const _: () = if !::papito::__codegen::has_required_props(
+                <#ty as ::papito::__codegen::Component>::REQUIRED_PROPS, &[ #(#names),* ]) {
+                panic!("a required prop is left out with `..`");
+            };
+            T { #(#fields,)* ..::std::default::Default::default() }
+        })
+    } else if fields.is_empty() {
+        quote!(())
+    } else {
+        quote!({
+            type T = <#ty as ::papito::__codegen::Component>::Props;
+            T { #(#fields),* }
+        })
+    };
+    let mut bindings = vec![];
+    for Attribute { name, value } in events {
+        if name.tokens.len() != 1 {
+            return Err(Error::new(name.span, format!("`{}` is not a valid event name", name.name)));
+        }
+        let event = name.as_path();
+        bindings.push(quote! {
+            let listener = ::papito::__codegen::Callback::from(#value);
+            comp.add_binding(move |instance: &#ty, bind| {
+                if bind {
+                    instance.#event().bind(&listener);
+                } else {
+                    instance.#event().unbind();
+                }
+            });
+        });
+    }
+    Ok(quote!({
+        let mut comp = ::papito::__codegen::comp::<#ty>(#props);
+        #(#bindings)*
+        ::papito::__codegen::h(comp)
+    }))
+}
+
+/// The `stdweb` event type of the DOM event `name`.
+fn event_type_of(name: &str) -> Option<&'static str> {
+    let event_ty = match name {
+        "click" => "ClickEvent",
+        "dblclick" => "DoubleClickEvent",
+        "mousedown" => "MouseDownEvent",
+        "mouseup" => "MouseUpEvent",
+        "mousemove" => "MouseMoveEvent",
+        "keypress" => "KeyPressEvent",
+        "keydown" => "KeyDownEvent",
+        "keyup" => "KeyUpEvent",
+        "progress" => "ProgressEvent",
+        "loadstart" => "LoadStartEvent",
+        "loadend" => "LoadEndEvent",
+        "load" => "ResourceLoadEvent",
+        "abort" => "ResourceAbortEvent",
+        "error" => "ResourceErrorEvent",
+        "hashchange" => "HashChangeEvent",
+        "popstate" => "PopStateEvent",
+        "change" => "ChangeEvent",
+        "resize" => "ResizeEvent",
+        "input" => "InputEvent",
+        "readystatechange" => "ReadyStateChangeEvent",
+        "focus" => "FocusEvent",
+        "blur" => "BlurEvent",
+        _ => return None
+    };
+    Some(event_ty)
+}
@@ -0,0 +1,119 @@
+use quote::Tokens;
+use proc_macro2::{Span, TokenTree, TokenNode, Delimiter};
+use syn::{Item, ItemImpl, ImplItem, ImplItemMethod, FnArg, ReturnType, Ident, Path, Type};
+use error::{Error, Result};
+use component::{watch_fn_name, watchers_trait_name};
+
+pub fn quote(item: Item) -> Result<Tokens> {
+    match item {
+        Item::Impl(mut item_impl) => {
+            let watchers = get_watchers_trait(&item_impl)?;
+            let watch_fns = quote_watch_fns(&mut item_impl, &watchers)?;
+            let self_ty = &item_impl.self_ty;
+            Ok(quote! {
+                #item_impl
+
+                impl #self_ty {
+                    #(#watch_fns)*
+                }
+            })
+        }
+        ref item => {
+            Err(Error::spanned(item, "`#[watchers]` attribute can only be used with an impl block"))
+        }
+    }
+}
+
+/// The trait with the watchers which do nothing, generated by `#[component]` for the impl type.
+fn get_watchers_trait(item_impl: &ItemImpl) -> Result<Ident> {
+    match *item_impl.self_ty {
+        Type::Path(ref ty) if ty.qself.is_none() => {
+            let component = ty.path.segments.iter().last().map(|it| it.ident);
+            Ok(watchers_trait_name(&component.expect("Impossible. A path has a segment")))
+        }
+        ref ty => Err(Error::spanned(ty, "`#[watchers]` can only be used on the impl block of a component"))
+    }
+}
+
+/// Strips the `#[watch(field)]` attribute from each watcher and creates the `_watch_{field}`
+/// method which calls it. Being an inherent method, it is called instead of the one which does
+/// nothing, generated by `#[component]`. The one which does nothing is referred to as well, so
+/// that watching a field the component does not have fails to compile.
+fn quote_watch_fns(item_impl: &mut ItemImpl, watchers: &Ident) -> Result<Vec<Tokens>> {
+    let mut watched = vec![];
+    let mut watch_fns = vec![];
+    for item in item_impl.items.iter_mut() {
+        if let ImplItem::Method(ref mut method_item) = *item {
+            let (field, span) = match parse_watched_field(method_item)? {
+                Some(field) => field,
+                None => continue
+            };
+            if watched.contains(&field) {
+                return Err(Error::new(span, format!("the field `{}` is already watched", field)));
+            }
+            method_item.attrs.retain(|it| !is_watch_attribute(it));
+            let ty = get_watched_type(method_item)?;
+            let fn_name = &method_item.sig.ident;
+            let watch_fn = watch_fn_name(&field);
+            let field_watch_fn = Ident::new(watch_fn.as_ref(), span);
+            watch_fns.push(quote! {
+                #[allow(dead_code)]
+                fn #watch_fn(&self, old: #ty, new: #ty) {
+                    let _: fn(&Self, #ty, #ty) = <Self as #watchers>::#field_watch_fn;
+                    self.#fn_name(old, new);
+                }
+            });
+            watched.push(field);
+        }
+    }
+    Ok(watch_fns)
+}
+
+fn is_watch_attribute(attr: &::syn::Attribute) -> bool {
+    attr.path == Path::from(Ident::from("watch"))
+}
+
+fn parse_watched_field(item: &ImplItemMethod) -> Result<Option<(String, Span)>> {
+    let attr = match item.attrs.iter().find(|it| is_watch_attribute(it)) {
+        Some(attr) => attr,
+        None => return Ok(None)
+    };
+    let inner = match attr.tts.clone().into_iter().next() {
+        Some(TokenTree { kind: TokenNode::Group(Delimiter::Parenthesis, inner), .. }) => inner,
+        _ => return Err(Error::spanned(attr, "expected the field to watch as `#[watch(field)]`"))
+    };
+    let mut inner = inner.into_iter();
+    match (inner.next(), inner.next()) {
+        (Some(TokenTree { span, kind: TokenNode::Term(term) }), None) => {
+            Ok(Some((term.as_str().to_string(), span)))
+        }
+        _ => Err(Error::spanned(attr, "expected the field to watch as `#[watch(field)]`"))
+    }
+}
+
+/// Checks that the watcher is `fn(&self, old: T, new: T)` and gets the `T`.
+fn get_watched_type(item: &ImplItemMethod) -> Result<Type> {
+    let sig = &item.sig;
+    let fn_name = &sig.ident;
+    if sig.decl.output != ReturnType::Default {
+        return Err(Error::spanned(&sig.decl.output, format!("the watcher `{}` cannot have a return type", fn_name)));
+    }
+    let args: Vec<_> = sig.decl.inputs.iter().collect();
+    if args.len() != 3 {
+        return Err(Error::spanned(fn_name, format!("the watcher `{}` must take `&self` along with the old and new values", fn_name)));
+    }
+    match *args[0] {
+        FnArg::SelfRef(ref self_arg) if self_arg.mutability.is_none() => {}
+        ref arg => {
+            return Err(Error::spanned(arg, format!("the first argument of `{}` can only be `&self`", fn_name)));
+        }
+    }
+    let old_ty = match *args[1] {
+        FnArg::Captured(ref arg) => &arg.ty,
+        ref arg => return Err(Error::spanned(arg, format!("the old value of `{}` must have an explicit type", fn_name)))
+    };
+    match *args[2] {
+        FnArg::Captured(ref arg) if arg.ty == *old_ty => Ok(arg.ty.clone()),
+        ref arg => Err(Error::spanned(arg, format!("the new value of `{}` must have the same type as the old one", fn_name)))
+    }
+}
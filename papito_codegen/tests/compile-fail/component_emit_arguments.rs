@@ -0,0 +1,11 @@
+extern crate papito_codegen;
+
+use papito_codegen::component;
+
+#[component] //~ ERROR `#[emit]` does not take any arguments
+struct Button {
+    #[emit(always)]
+    on_click: ::std::rc::Rc<Fn()>
+}
+
+fn main() {}
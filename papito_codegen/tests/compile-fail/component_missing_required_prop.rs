@@ -0,0 +1,27 @@
+#[macro_use]
+extern crate papito_dom;
+extern crate papito_codegen;
+
+use papito_dom::{Lifecycle, Render};
+use papito_dom::prelude::VNode;
+use papito_codegen::component;
+
+#[component]
+struct Button {
+    #[prop]
+    label: String,
+    #[prop(optional)]
+    disabled: bool
+}
+
+impl Lifecycle for Button {}
+
+impl Render for Button {
+    fn render(&self) -> VNode {
+        h!("button")
+    }
+}
+
+fn main() {
+    h!(comp Button, { disabled => true }); //~ ERROR missing field `label`
+}
@@ -0,0 +1,10 @@
+extern crate papito_codegen;
+
+use papito_codegen::component;
+
+#[component] //~ ERROR `#[component]` can only be used on a struct
+enum Button {
+    Primary
+}
+
+fn main() {}
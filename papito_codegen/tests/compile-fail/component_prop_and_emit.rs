@@ -0,0 +1,12 @@
+extern crate papito_codegen;
+
+use papito_codegen::component;
+
+#[component] //~ ERROR the field `active` can only have one of `#[prop]`, `#[state]` or `#[emit]`
+struct Button {
+    #[prop]
+    #[emit]
+    active: bool
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+extern crate papito_codegen;
+
+use papito_codegen::component;
+
+#[component] //~ ERROR only private fields are allowed in a `#[component]`
+struct Button {
+    pub active: bool
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+extern crate papito_codegen;
+
+use papito_codegen::component;
+
+#[component] //~ ERROR tuple structs are not allowed as a `#[component]`
+struct Button(String);
+
+fn main() {}
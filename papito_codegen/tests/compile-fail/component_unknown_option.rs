@@ -0,0 +1,10 @@
+extern crate papito_codegen;
+
+use papito_codegen::component;
+
+#[component(save)] //~ ERROR `#[component]` only supports `save_state`
+struct Counter {
+    count: u32
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+extern crate papito_codegen;
+
+use papito_codegen::component;
+
+#[component] //~ ERROR `#[prop]` only supports `optional` or `default = expr`
+struct Button {
+    #[prop(always)]
+    active: bool
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+extern crate papito_codegen;
+
+use papito_codegen::component;
+
+#[component] //~ ERROR `#[state]` only supports `init = expr`
+struct Button {
+    #[state(default = true)]
+    active: bool
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+extern crate papito_codegen;
+
+use papito_codegen::computed;
+
+struct TodoList;
+
+impl TodoList {
+    #[computed] //~ ERROR the computed method `remaining` cannot have any more arguments
+    fn remaining(&self, done: bool) -> usize {
+        0
+    }
+}
+
+fn main() {}
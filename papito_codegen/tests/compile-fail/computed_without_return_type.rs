@@ -0,0 +1,12 @@
+extern crate papito_codegen;
+
+use papito_codegen::computed;
+
+struct TodoList;
+
+impl TodoList {
+    #[computed] //~ ERROR the computed method `remaining` must return a value
+    fn remaining(&self) {}
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+extern crate papito_codegen;
+
+use papito_codegen::{events, event};
+
+struct Button;
+
+#[events] //~ ERROR the event method `on_click` has no arguments
+impl Button {
+    #[event]
+    fn on_click() {}
+}
+
+fn main() {}
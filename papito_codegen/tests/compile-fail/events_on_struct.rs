@@ -0,0 +1,8 @@
+extern crate papito_codegen;
+
+use papito_codegen::events;
+
+#[events] //~ ERROR `#[events]` attribute can only be used with an impl block
+struct Button;
+
+fn main() {}
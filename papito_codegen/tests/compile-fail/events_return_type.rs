@@ -0,0 +1,15 @@
+extern crate papito_codegen;
+
+use papito_codegen::{events, event};
+
+struct Button;
+
+#[events] //~ ERROR the event method `on_click` cannot have a return type
+impl Button {
+    #[event]
+    fn on_click(&self, _: u32) -> bool {
+        true
+    }
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+extern crate papito_codegen;
+
+use papito_codegen::{events, event};
+
+struct Button;
+
+#[events] //~ ERROR the event method `on_click` cannot have more than 2 arguments
+impl Button {
+    #[event]
+    fn on_click(&self, _: u32, count: u32) {}
+}
+
+fn main() {}
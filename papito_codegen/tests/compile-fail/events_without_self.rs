@@ -0,0 +1,13 @@
+extern crate papito_codegen;
+
+use papito_codegen::{events, event};
+
+struct Button;
+
+#[events] //~ ERROR the first argument of `on_click` can only be `&self` or `&mut self`
+impl Button {
+    #[event]
+    fn on_click(button: Button, _: u32) {}
+}
+
+fn main() {}
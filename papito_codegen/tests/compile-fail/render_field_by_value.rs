@@ -0,0 +1,18 @@
+extern crate papito_codegen;
+
+use papito_codegen::render;
+
+struct Button;
+
+trait Render {
+    fn render(&self) -> String;
+}
+
+#[render] //~ ERROR `self.label` cannot be moved out of the data
+impl Render for Button {
+    fn render(&self) -> String {
+        self.label
+    }
+}
+
+fn main() {}
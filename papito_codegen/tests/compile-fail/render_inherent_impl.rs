@@ -0,0 +1,12 @@
+extern crate papito_codegen;
+
+use papito_codegen::render;
+
+struct Button;
+
+#[render] //~ ERROR `#[render]` can only be used on an `impl Render for` the component
+impl Button {
+    fn render(&self) {}
+}
+
+fn main() {}
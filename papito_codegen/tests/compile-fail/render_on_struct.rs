@@ -0,0 +1,8 @@
+extern crate papito_codegen;
+
+use papito_codegen::render;
+
+#[render] //~ ERROR `#[render]` attribute can only be used with an impl block
+struct Button;
+
+fn main() {}
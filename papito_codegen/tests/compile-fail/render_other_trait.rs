@@ -0,0 +1,16 @@
+extern crate papito_codegen;
+
+use papito_codegen::render;
+
+struct Button;
+
+trait Draw {
+    fn render(&self);
+}
+
+#[render] //~ ERROR `#[render]` can only be used on an `impl Render for` the component
+impl Draw for Button {
+    fn render(&self) {}
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+extern crate papito_codegen;
+
+use papito_codegen::render;
+
+struct Button;
+
+trait Render {}
+
+#[render] //~ ERROR `#[render]` requires the `render` method to be implemented
+impl Render for Button {}
+
+fn main() {}
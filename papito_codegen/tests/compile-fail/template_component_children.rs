@@ -0,0 +1,11 @@
+extern crate papito_codegen;
+
+use papito_codegen::html;
+
+fn main() {
+    html! { //~ ERROR the component `Button` cannot have any children
+        <Button>
+            "Click"
+        </Button>
+    };
+}
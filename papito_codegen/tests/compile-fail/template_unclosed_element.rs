@@ -0,0 +1,10 @@
+extern crate papito_codegen;
+
+use papito_codegen::html;
+
+fn main() {
+    html! { //~ ERROR the element `<div>` is not closed
+        <div>
+            "Hello"
+    };
+}
@@ -0,0 +1,9 @@
+extern crate papito_codegen;
+
+use papito_codegen::html;
+
+fn main() {
+    html! { //~ ERROR unknown event `@tap`
+        <button @tap={|_| {}}></button>
+    };
+}
@@ -0,0 +1,13 @@
+extern crate papito_codegen;
+
+use papito_codegen::{watchers, watch};
+
+struct Counter;
+
+#[watchers] //~ ERROR the new value of `on_count` must have the same type as the old one
+impl Counter {
+    #[watch(count)]
+    fn on_count(&self, old: u32, new: String) {}
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+extern crate papito_codegen;
+
+use papito_codegen::{watchers, watch};
+
+struct Counter;
+
+#[watchers] //~ ERROR the field `count` is already watched
+impl Counter {
+    #[watch(count)]
+    fn on_count(&self, old: u32, new: u32) {}
+
+    #[watch(count)]
+    fn log_count(&self, old: u32, new: u32) {}
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+extern crate papito_codegen;
+
+use papito_codegen::{watchers, watch};
+
+struct Counter;
+
+// The watchers which do nothing, as generated by `#[component]` for the field `count`
+trait _CounterWatchers {
+    fn _watch_count(&self, _old: u32, _new: u32) {}
+}
+
+impl _CounterWatchers for Counter {}
+
+#[watchers] //~ ERROR cannot find method or associated constant `_watch_cuont`
+impl Counter {
+    #[watch(cuont)]
+    fn on_count(&self, old: u32, new: u32) {}
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+extern crate papito_codegen;
+
+use papito_codegen::watch;
+
+struct Counter;
+
+impl Counter {
+    #[watch(count)] //~ ERROR `#[watch(field)]` can only be used within an impl block marked with `#[watchers]`
+    fn on_count(&self, old: u32, new: u32) {}
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+extern crate compiletest_rs as compiletest;
+
+use std::path::PathBuf;
+
+#[test]
+fn should_report_spanned_compile_errors() {
+    let mut config = compiletest::Config::default();
+    config.mode = "compile-fail".parse().expect("Invalid mode");
+    config.src_base = PathBuf::from("tests/compile-fail");
+    config.link_deps();
+    config.clean_rmeta();
+    compiletest::run_tests(&config);
+}
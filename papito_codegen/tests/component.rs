@@ -1,5 +1,3 @@
-#![feature(proc_macro, conservative_impl_trait)]
-
 extern crate papito;
 #[macro_use]
 extern crate papito_dom;
@@ -10,7 +8,7 @@ extern crate stdweb;
 
 use papito::prelude::{Lifecycle, Render};
 use papito_dom::prelude::VNode;
-use papito_codegen::{component, render, events, event};
+use papito_codegen::{component, render, events, computed, watchers};
 use stdweb::web::event::ClickEvent;
 
 #[test]
@@ -67,4 +65,237 @@ fn should_create_event_wrappers() {
     }
 
     h!(comp Button);
-}
\ No newline at end of file
+}
+#[test]
+fn should_bind_emitted_events() {
+    use papito_dom::Callback;
+
+    #[derive(Lifecycle)]
+    #[component]
+    struct Button {
+        #[emit]
+        on_toggle: Callback<bool>
+    }
+
+    #[render]
+    impl Render for Button {
+        fn render(&self) -> VNode {
+            h!("button", h!("Click"))
+        }
+    }
+
+    h!(comp Button, [ on_toggle => |_: bool| {} ]);
+}
+
+#[test]
+fn should_read_data_fields_within_render() {
+    #[derive(Lifecycle)]
+    #[component]
+    struct Button {
+        #[prop]
+        style: String,
+        click: bool
+    }
+
+    #[render]
+    impl Render for Button {
+        fn render(&self) -> VNode {
+            let label = if self.click.clone() { "Clicked" } else { "Click" };
+            h!("button", { "style" => self.style.clone() }, h!(label))
+        }
+    }
+
+    h!(comp Button, { style => "color: black;".to_string() });
+}
+
+#[test]
+fn should_set_state_within_event_handlers() {
+    #[derive(Lifecycle)]
+    #[component]
+    struct Button {
+        click: bool
+    }
+
+    #[events]
+    impl Button {
+        #[event]
+        fn on_click(&self, _: ClickEvent) {
+            let inv = !self.click();
+            self.set_click(inv);
+        }
+    }
+
+    #[render]
+    impl Render for Button {
+        fn render(&self) -> VNode {
+            let label = if self.click.clone() { "Clicked" } else { "Click" };
+            h!("button", [ self.on_click() ], h!(label))
+        }
+    }
+
+    h!(comp Button);
+}
+
+#[test]
+fn should_default_the_props_not_given() {
+    use papito_dom::prelude::RenderToString;
+
+    #[derive(Lifecycle)]
+    #[component]
+    struct Button {
+        #[prop]
+        label: String,
+        #[prop(default = "color: black;".to_string())]
+        style: String,
+        #[prop(optional)]
+        disabled: bool,
+        #[state(init = 1)]
+        clicks: u32
+    }
+
+    #[render]
+    impl Render for Button {
+        fn render(&self) -> VNode {
+            let label = format!("{} {}", &self.label, &self.clicks);
+            h!("button", { "style" => self.style.clone(), "disabled" => self.disabled.clone() }, h!(label))
+        }
+    }
+
+    let mut node = h!(comp Button, { label => "Click".to_string(), style => None, disabled => None });
+    assert_eq!(node.render_to_string(), r#"<button style="color: black;">Click 1</button>"#);
+
+    // The optional props are left out next to the required one with `..`
+    let mut node = h!(comp Button, { label => "Click".to_string(), disabled => true, .. });
+    assert_eq!(node.render_to_string(), r#"<button style="color: black;" disabled>Click 1</button>"#);
+
+    #[derive(Lifecycle)]
+    #[component]
+    struct Icon {
+        #[prop(default = 16)]
+        size: u32,
+        #[prop(optional)]
+        title: String
+    }
+
+    #[render]
+    impl Render for Icon {
+        fn render(&self) -> VNode {
+            h!("i", { "data-size" => self.size.to_string(), "title" => self.title.clone() })
+        }
+    }
+
+    // Without any required props, the rest are left out with `..`
+    let mut node = h!(comp Icon, { title => "Close".to_string(), .. });
+    assert_eq!(node.render_to_string(), r#"<i data-size="16" title="Close"></i>"#);
+}
+
+#[test]
+fn should_compare_each_of_the_props() {
+    use papito_dom::Component;
+
+    #[derive(Lifecycle)]
+    #[component]
+    struct Icon {
+        #[prop]
+        name: String,
+        #[prop(default = 16)]
+        size: u32,
+        #[prop(optional)]
+        title: String
+    }
+
+    #[render]
+    impl Render for Icon {
+        fn render(&self) -> VNode {
+            h!("i", { "class" => self.name.clone() })
+        }
+    }
+
+    type Props = <Icon as Component>::Props;
+    let props = |name: &str, size: Option<u32>, title: Option<&str>| Props {
+        name: papito_dom::prop(name.to_string()),
+        size,
+        title: title.map(|it| it.to_string())
+    };
+    let icon = Icon::create(props("close", None, None), Box::new(|| {}));
+    assert!(icon.eq_props(&props("close", None, None)));
+    assert!(icon.eq_props(&props("close", Some(16), Some(""))));
+    assert!(!icon.eq_props(&props("open", None, None)));
+    assert!(!icon.eq_props(&props("close", Some(24), None)));
+    assert!(!icon.eq_props(&props("close", None, Some("Close"))));
+}
+
+#[test]
+fn should_cache_computed_values() {
+    use papito_dom::prelude::RenderToString;
+
+    #[derive(Lifecycle)]
+    #[component]
+    struct TodoList {
+        #[prop]
+        items: Vec<(String, bool)>
+    }
+
+    impl TodoList {
+        #[computed]
+        fn remaining(&self) -> usize {
+            self.items.iter().filter(|&&(_, done)| !done).count()
+        }
+
+        #[computed]
+        fn summary(&self) -> String {
+            format!("{} of {} remaining", self.remaining(), self.items.len())
+        }
+    }
+
+    #[render]
+    impl Render for TodoList {
+        fn render(&self) -> VNode {
+            h!("p", h!(self.summary()))
+        }
+    }
+
+    let items = vec![("Write".to_string(), true), ("Test".to_string(), false)];
+    let mut node = h!(comp TodoList, { items => items });
+    assert_eq!(node.render_to_string(), "<p>1 of 2 remaining</p>");
+}
+
+#[test]
+fn should_call_watchers_with_old_and_new_values() {
+    use std::cell::RefCell;
+    use papito_dom::prelude::RenderToString;
+
+    thread_local!(static CHANGES: RefCell<Vec<(u32, u32)>> = RefCell::new(vec![]));
+
+    #[component]
+    struct Counter {
+        count: u32
+    }
+
+    #[watchers]
+    impl Counter {
+        #[watch(count)]
+        fn on_count(&self, old: u32, new: u32) {
+            CHANGES.with(|it| it.borrow_mut().push((old, new)));
+        }
+    }
+
+    impl Lifecycle for Counter {
+        fn created(&self) {
+            self.set_count(1);
+            self.set_count(1);
+            self.set_count(2);
+        }
+    }
+
+    #[render]
+    impl Render for Counter {
+        fn render(&self) -> VNode {
+            h!(self.count.to_string())
+        }
+    }
+
+    let mut node = h!(comp Counter);
+    node.render_to_string();
+    CHANGES.with(|it| assert_eq!(*it.borrow(), vec![(0, 1), (1, 2)]));
+}
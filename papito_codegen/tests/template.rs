@@ -0,0 +1,84 @@
+extern crate papito;
+#[macro_use]
+extern crate papito_dom;
+#[macro_use]
+extern crate papito_codegen;
+extern crate stdweb;
+
+use papito::prelude::Render;
+use papito_dom::prelude::VNode;
+use papito_codegen::{component, render, html, template};
+
+#[test]
+fn should_create_elements_from_template() {
+    let node = html! {
+        <div class="container" data-id={"1"}>
+            <input type="checkbox" checked />
+            "Hello"
+        </div>
+    };
+    assert_eq!(
+        node,
+        h!("div", { "class" => "container", "data-id" => "1" },
+            h!("input", { "type" => "checkbox", "checked" => true }),
+            h!("Hello"))
+    );
+}
+
+#[test]
+fn should_interpolate_strings_and_vnodes() {
+    let name = "World".to_string();
+    let inner = h!("b", _);
+    let node = template! {
+        <p>{name} {inner}</p>
+    };
+    assert_eq!(node.to_string(), "<p>World<b></b></p>");
+}
+
+#[test]
+fn should_expand_if_and_for_directives() {
+    let items = vec!["a", "b"];
+    let is_empty = items.is_empty();
+    let node = html! {
+        <ul>
+            for item in items.iter() {
+                <li>{*item}</li>
+            }
+        </ul>
+        if is_empty {
+            <p>"Empty"</p>
+        } else {
+            <p>"Full"</p>
+        }
+    };
+    assert_eq!(node.to_string(), "<ul><li>a</li><li>b</li></ul><p>Full</p>");
+}
+
+#[test]
+fn should_create_components_from_template() {
+    use papito_dom::prelude::RenderToString;
+
+    #[derive(Lifecycle)]
+    #[component]
+    struct Button {
+        #[prop]
+        label: String,
+        #[prop(optional)]
+        disabled: bool
+    }
+
+    #[render]
+    impl Render for Button {
+        fn render(&self) -> VNode {
+            html! { <button disabled={self.disabled.clone()}>{self.label.clone()}</button> }
+        }
+    }
+
+    let mut node = html! {
+        <div>
+            <Button label={"Click".to_string()} disabled={true} />
+            <Button label={"Click".to_string()} .. />
+        </div>
+    };
+    assert_eq!(node.render_to_string(), "<div><button disabled>Click</button><button>Click</button></div>");
+}
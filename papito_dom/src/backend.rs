@@ -0,0 +1,196 @@
+use events::DOMEvent;
+use velement::AttrValue;
+
+/// The platform onto which the virtual DOM is patched. The diffing done by `DOMPatch` only ever
+/// talks to the DOM through this trait, so that the same reconciler can drive the browser DOM
+/// as well as an in-memory tree on a non-browser host.
+pub trait Backend: 'static {
+    /// A handle to any node (element or text) created by the backend.
+    type Node: Clone + 'static;
+
+    fn create_element(&self, tag: &str) -> Self::Node;
+
+    /// Creates an element within the namespace given by its uri, e.g. for svg elements.
+    fn create_element_ns(&self, namespace: &str, tag: &str) -> Self::Node;
+
+    fn create_text(&self, content: &str) -> Self::Node;
+
+    fn set_text_content(&self, node: &Self::Node, content: &str);
+
+    /// Creates the nodes out of a trusted html fragment. They are not yet attached to any parent.
+    fn create_raw_html(&self, html: &str) -> Vec<Self::Node>;
+
+    fn insert_before(&self, parent: &Self::Node, child: &Self::Node, next: &Self::Node);
+
+    fn append_child(&self, parent: &Self::Node, child: &Self::Node);
+
+    fn remove_child(&self, parent: &Self::Node, child: &Self::Node);
+
+    fn set_attribute(&self, node: &Self::Node, name: &str, value: &str);
+
+    fn remove_attribute(&self, node: &Self::Node, name: &str);
+
+    fn add_class(&self, node: &Self::Node, class: &str);
+
+    fn remove_class(&self, node: &Self::Node, class: &str);
+
+    fn set_style(&self, node: &Self::Node, property: &str, value: &str);
+
+    fn remove_style(&self, node: &Self::Node, property: &str);
+
+    /// Sets the live property of an element, such as the `value` of an input. An `Absent` value
+    /// resets the property.
+    fn set_property(&self, node: &Self::Node, name: &str, value: &AttrValue);
+
+    fn add_listener(&self, node: &Self::Node, listener: &mut DOMEvent);
+
+    fn remove_listener(&self, node: &Self::Node, listener: &mut DOMEvent);
+
+    /// The child nodes of the `node` in order, including the text nodes. Used to adopt the nodes
+    /// rendered by the server.
+    fn child_nodes(&self, node: &Self::Node) -> Vec<Self::Node>;
+
+    /// The tag name of the `node` if it is an element.
+    fn tag_name(&self, node: &Self::Node) -> Option<String>;
+
+    /// The content of the `node` if it is a text node.
+    fn text(&self, node: &Self::Node) -> Option<String>;
+
+    /// Inserts the `child` before `next` if provided, otherwise appends it to the `parent`.
+    fn insert(&self, parent: &Self::Node, child: &Self::Node, next: Option<&Self::Node>) {
+        if let Some(next) = next {
+            self.insert_before(parent, child, next);
+        } else {
+            self.append_child(parent, child);
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub use self::wasm::StdwebBackend;
+
+#[cfg(target_arch = "wasm32")]
+mod wasm {
+    use super::Backend;
+    use stdweb::web::{document, Element, IElement, INode, Node};
+    use stdweb::unstable::TryInto;
+    use events::DOMEvent;
+    use velement::AttrValue;
+
+    /// Patches the browser DOM via `stdweb`.
+    #[derive(Debug, Clone, Copy, Default)]
+    pub struct StdwebBackend;
+
+    fn as_element(node: &Node) -> Element {
+        node.clone().try_into()
+            .expect("The node is not an element")
+    }
+
+    impl Backend for StdwebBackend {
+        type Node = Node;
+
+        fn create_element(&self, tag: &str) -> Node {
+            document().create_element(tag).unwrap().into()
+        }
+
+        fn create_element_ns(&self, namespace: &str, tag: &str) -> Node {
+            document().create_element_ns(namespace, tag).unwrap().into()
+        }
+
+        fn create_text(&self, content: &str) -> Node {
+            document().create_text_node(content).into()
+        }
+
+        fn set_text_content(&self, node: &Node, content: &str) {
+            node.set_text_content(content);
+        }
+
+        fn create_raw_html(&self, html: &str) -> Vec<Node> {
+            // Parsed by the browser as the innerHTML of a detached container
+            let container = document().create_element("template").unwrap();
+            js! { @(no_return)
+                @{&container}.innerHTML = @{html};
+            }
+            let content: Node = js!( return @{&container}.content; ).try_into().unwrap();
+            let nodes: Vec<Node> = content.child_nodes().iter().collect();
+            for node in nodes.iter() {
+                content.remove_child(node).unwrap();
+            }
+            nodes
+        }
+
+        fn insert_before(&self, parent: &Node, child: &Node, next: &Node) {
+            parent.insert_before(child, next).unwrap();
+        }
+
+        fn append_child(&self, parent: &Node, child: &Node) {
+            parent.append_child(child);
+        }
+
+        fn remove_child(&self, parent: &Node, child: &Node) {
+            parent.remove_child(child).unwrap();
+        }
+
+        fn set_attribute(&self, node: &Node, name: &str, value: &str) {
+            as_element(node).set_attribute(name, value).unwrap();
+        }
+
+        fn remove_attribute(&self, node: &Node, name: &str) {
+            as_element(node).remove_attribute(name);
+        }
+
+        fn add_class(&self, node: &Node, class: &str) {
+            as_element(node).class_list().add(class).unwrap();
+        }
+
+        fn remove_class(&self, node: &Node, class: &str) {
+            as_element(node).class_list().remove(class).unwrap();
+        }
+
+        fn set_style(&self, node: &Node, property: &str, value: &str) {
+            js! { @(no_return)
+                @{node}.style.setProperty(@{property}, @{value});
+            }
+        }
+
+        fn remove_style(&self, node: &Node, property: &str) {
+            js! { @(no_return)
+                @{node}.style.removeProperty(@{property});
+            }
+        }
+
+        fn set_property(&self, node: &Node, name: &str, value: &AttrValue) {
+            match *value {
+                AttrValue::Str(ref value) => js! { @(no_return)
+                    @{node}[@{name}] = @{value.as_ref()};
+                },
+                AttrValue::Bool(value) => js! { @(no_return)
+                    @{node}[@{name}] = @{value};
+                },
+                AttrValue::Absent => js! { @(no_return)
+                    @{node}[@{name}] = null;
+                }
+            }
+        }
+
+        fn add_listener(&self, node: &Node, listener: &mut DOMEvent) {
+            listener.attach(&as_element(node));
+        }
+
+        fn remove_listener(&self, _: &Node, listener: &mut DOMEvent) {
+            listener.detach();
+        }
+
+        fn child_nodes(&self, node: &Node) -> Vec<Node> {
+            node.child_nodes().iter().collect()
+        }
+
+        fn tag_name(&self, node: &Node) -> Option<String> {
+            js!( return @{node}.nodeType === 1 ? @{node}.localName : null; ).try_into().unwrap()
+        }
+
+        fn text(&self, node: &Node) -> Option<String> {
+            js!( return @{node}.nodeType === 3 ? @{node}.nodeValue : null; ).try_into().unwrap()
+        }
+    }
+}
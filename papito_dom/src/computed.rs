@@ -0,0 +1,63 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::fmt::{Formatter, self};
+use std::fmt::Debug;
+use indexmap::IndexMap;
+
+/// The cached results of the `#[computed]` methods of a component along with the names of the
+/// fields (or the other computed methods) that each of them reads.
+#[derive(Default)]
+pub struct ComputedCache {
+    values: RefCell<IndexMap<&'static str, Cached>>,
+}
+
+/// A cached result along with the names that it reads.
+type Cached = (Box<Any>, &'static [&'static str]);
+
+impl ComputedCache {
+    pub fn new() -> ComputedCache {
+        ComputedCache::default()
+    }
+
+    /// Gets the cached result of `name`, or computes it when it is invalidated. The cache is not
+    /// borrowed while computing, so that a computed method may read the other ones.
+    pub fn get_or_compute<T, F>(&self, name: &'static str, reads: &'static [&'static str], compute: F) -> T where
+        T: Clone + 'static,
+        F: FnOnce() -> T {
+        let cached = self.values.borrow().get(name)
+            .and_then(|(value, _)| value.downcast_ref::<T>().cloned());
+        if let Some(value) = cached {
+            return value;
+        }
+        let value = compute();
+        self.values.borrow_mut().insert(name, (Box::new(value.clone()), reads));
+        value
+    }
+
+    /// Invalidates the results which read the `field`, along with the ones which read them in turn.
+    pub fn invalidate(&self, field: &str) {
+        let mut values = self.values.borrow_mut();
+        let mut invalidated = vec![field.to_string()];
+        while let Some(name) = invalidated.pop() {
+            let reads_name: Vec<_> = values.iter()
+                .filter(|&(_, &(_, reads))| reads.iter().any(|it| *it == name))
+                .map(|(&it, _)| it)
+                .collect();
+            for it in reads_name {
+                values.retain(|&key, _| key != it);
+                invalidated.push(it.to_string());
+            }
+        }
+    }
+
+    pub fn is_cached(&self, name: &str) -> bool {
+        self.values.borrow().contains_key(name)
+    }
+}
+
+impl Debug for ComputedCache {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let names: Vec<_> = self.values.borrow().keys().cloned().collect();
+        write!(f, "ComputedCache({:?})", names)
+    }
+}
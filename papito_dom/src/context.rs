@@ -0,0 +1,136 @@
+use std::any::{Any, TypeId};
+use std::cell::{Ref, RefCell};
+use std::rc::{Rc, Weak};
+use std::fmt::Debug;
+use std::fmt::{Formatter, self};
+
+/// A value provided by a component to all of its descendants. The descendants which consume it
+/// are re-rendered whenever the value is changed.
+pub struct Context<T>(Rc<ContextData<T>>);
+
+struct ContextData<T> {
+    value: RefCell<T>,
+    consumers: RefCell<Vec<Weak<Fn()>>>,
+}
+
+impl<T> Context<T> {
+    fn new(value: T) -> Context<T> {
+        Context(Rc::new(ContextData {
+            value: RefCell::new(value),
+            consumers: RefCell::new(vec![]),
+        }))
+    }
+
+    pub fn get(&self) -> Ref<'_, T> {
+        self.0.value.borrow()
+    }
+
+    pub fn set(&self, value: T) {
+        *self.0.value.borrow_mut() = value;
+        self.notify();
+    }
+
+    pub fn update<F: FnOnce(&mut T)>(&self, update: F) {
+        update(&mut *self.0.value.borrow_mut());
+        self.notify();
+    }
+
+    fn subscribe(&self, notifier: &Rc<Fn()>) {
+        let mut consumers = self.0.consumers.borrow_mut();
+        // Also drops the consumers that are already destroyed
+        consumers.retain(|it| it.upgrade().map(|it| !Rc::ptr_eq(&it, notifier)).unwrap_or(false));
+        consumers.push(Rc::downgrade(notifier));
+    }
+
+    fn notify(&self) {
+        let consumers: Vec<_> = self.0.consumers.borrow().iter()
+            .filter_map(|it| it.upgrade())
+            .collect();
+        for notifier in consumers {
+            notifier();
+        }
+    }
+}
+
+impl<T> Clone for Context<T> {
+    fn clone(&self) -> Self {
+        Context(self.0.clone())
+    }
+}
+
+impl<T: Debug> Debug for Context<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Context({:?})", self.get())
+    }
+}
+
+pub type Provided = (TypeId, Rc<Any>);
+
+/// A component which is being created or rendered.
+struct Frame {
+    notifier: Rc<Fn()>,
+    provided: Vec<Provided>,
+}
+
+thread_local! {
+    static PROVIDED: RefCell<Vec<Provided>> = const { RefCell::new(Vec::new()) };
+    static CURRENT: RefCell<Vec<Frame>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Provides the `value` to the descendants of the component. Can only be called while the
+/// component is created.
+pub fn provide<T: 'static>(value: T) -> Context<T> {
+    let context = Context::new(value);
+    CURRENT.with(|current| {
+        let mut current = current.borrow_mut();
+        let frame = current.last_mut()
+            .expect("A context can only be provided while a component is created");
+        frame.provided.push((TypeId::of::<T>(), Rc::new(context.clone())));
+    });
+    context
+}
+
+/// Finds the value of type `T` provided by the nearest ancestor. The component calling it, either
+/// when created or rendered, is re-rendered whenever the value is changed.
+pub fn consume<T: 'static>() -> Option<Context<T>> {
+    let context = PROVIDED.with(|provided| {
+        provided.borrow().iter().rev()
+            .find(|(type_id, _)| *type_id == TypeId::of::<T>())
+            .and_then(|(_, context)| context.downcast_ref::<Context<T>>().cloned())
+    });
+    if let Some(ref context) = context {
+        CURRENT.with(|current| {
+            if let Some(frame) = current.borrow().last() {
+                context.subscribe(&frame.notifier);
+            }
+        });
+    }
+    context
+}
+
+/// Runs `f` as the component with the `notifier`. Returns the contexts provided by it.
+pub fn as_current<F, R>(notifier: Rc<Fn()>, f: F) -> (R, Vec<Provided>) where
+    F: FnOnce() -> R {
+    CURRENT.with(|current| current.borrow_mut().push(Frame { notifier, provided: vec![] }));
+    let result = f();
+    let frame = CURRENT.with(|current| current.borrow_mut().pop())
+        .expect("Impossible. The frame was pushed above");
+    (result, frame.provided)
+}
+
+/// Runs `f` with the `provided` contexts visible to the components created within.
+pub fn with_provided<F, R>(provided: &[Provided], f: F) -> R where
+    F: FnOnce() -> R {
+    if provided.is_empty() {
+        return f();
+    }
+    let len = PROVIDED.with(|it| {
+        let mut it = it.borrow_mut();
+        let len = it.len();
+        it.extend(provided.iter().cloned());
+        len
+    });
+    let result = f();
+    PROVIDED.with(|it| it.borrow_mut().truncate(len));
+    result
+}
@@ -0,0 +1,163 @@
+use std::fmt::Display;
+use std::fmt::{Formatter, self};
+use velement::VElement;
+use vnode::VNode;
+use escape::Escaped;
+use std::task::{Context as TaskContext, Poll};
+use std::io::{self, Write};
+use traits::{ServerRender, ServerStream, CollectStates, ServerLoad};
+use head::{self, Head};
+use state::{self, SavedStates};
+use CowStr;
+
+/// A complete html document rendered on the server, with the app mounted within the element with
+/// the `root_id`. The components add to its head while they are rendered. The states they save for
+/// the client are written in a script at the end of the body.
+pub struct Document {
+    app: VNode,
+    root_id: CowStr,
+    head: Head,
+    states: SavedStates,
+}
+
+impl Document {
+    pub fn new<T: Into<CowStr>>(app: VNode, root_id: T) -> Document {
+        let mut head = Head::new();
+        head.insert("charset".into(), VElement::from(("meta", vec![("charset", "utf-8")])));
+        Document {
+            app,
+            root_id: root_id.into(),
+            head,
+            states: SavedStates::new(),
+        }
+    }
+
+    /// Adds an entry to the head, which may be replaced by a component adding one with the same
+    /// `key`.
+    pub fn head<K, E>(mut self, key: K, entry: E) -> Document where
+        K: Into<CowStr>,
+        E: Into<VElement> {
+        self.head.insert(key.into(), entry.into());
+        self
+    }
+
+    fn save_states(&mut self) {
+        self.states = SavedStates::new();
+        self.app.collect_states(&mut vec![0], &mut self.states);
+    }
+}
+
+impl ServerRender for Document {
+    fn server_render(&mut self) {
+        let app = &mut self.app;
+        head::collect(&mut self.head, || app.server_render());
+        self.save_states();
+    }
+}
+
+impl ServerLoad for Document {
+    fn poll_load(&mut self, cx: &mut TaskContext) -> Poll<()> {
+        let app = &mut self.app;
+        let mut loaded = Poll::Pending;
+        head::collect(&mut self.head, || loaded = app.poll_load(cx));
+        if loaded.is_ready() {
+            self.save_states();
+        }
+        loaded
+    }
+}
+
+/// A streamed document writes its head just before the first of the app, so it has the entries
+/// added by the components rendered till then.
+impl ServerStream for Document {
+    fn server_stream(self, writer: &mut Write) -> io::Result<()> {
+        let Document { app, root_id, mut head, .. } = self;
+        let mut writer = StreamedDocument {
+            writer,
+            root_id,
+            is_opened: false,
+        };
+        let mut streamed = Ok(());
+        let mut states = SavedStates::new();
+        head::collect(&mut head, || {
+            let (result, saved) = state::collect_streamed(|| app.server_stream(&mut writer));
+            // An app which writes nothing still has a head
+            streamed = result.and_then(|_| writer.open());
+            states = saved;
+        });
+        streamed?;
+        write!(writer.writer, "</div>{}</body></html>", StateScript(&states))
+    }
+}
+
+impl Display for Document {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let open = OpenDocument(&self.head, &self.root_id);
+        write!(f, "{}{}</div>{}</body></html>", open, self.app, StateScript(&self.states))
+    }
+}
+
+/// Displays the document up to the root element of the app.
+struct OpenDocument<'a>(&'a Head, &'a str);
+
+impl<'a> Display for OpenDocument<'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "<!DOCTYPE html><html><head>")?;
+        for entry in self.0.values() {
+            write!(f, "{}", entry)?;
+        }
+        write!(f, "</head><body><div id=\"{}\">", Escaped(self.1))
+    }
+}
+
+/// Writes the document up to the root element of the app, with the head collected so far, before
+/// the first of the app is written.
+struct StreamedDocument<'a> {
+    writer: &'a mut Write,
+    root_id: CowStr,
+    is_opened: bool,
+}
+
+impl<'a> StreamedDocument<'a> {
+    fn open(&mut self) -> io::Result<()> {
+        if self.is_opened {
+            return Ok(());
+        }
+        self.is_opened = true;
+        let StreamedDocument { ref mut writer, ref root_id, .. } = *self;
+        head::write_collected(|head| write!(writer, "{}", OpenDocument(head, root_id)))
+    }
+}
+
+impl<'a> Write for StreamedDocument<'a> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.open()?;
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Displays the script with the saved states, if any. Read by `App::hydrate` on the client.
+#[cfg_attr(not(feature = "save_state"), allow(dead_code))]
+struct StateScript<'a>(&'a SavedStates);
+
+#[cfg(feature = "save_state")]
+impl<'a> Display for StateScript<'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if self.0.is_empty() {
+            return Ok(());
+        }
+        write!(f, "<script>window.__papito_state__ = {};</script>", state::to_json(self.0))
+    }
+}
+
+/// The states are only handed off with the `save_state` feature.
+#[cfg(not(feature = "save_state"))]
+impl<'a> Display for StateScript<'a> {
+    fn fmt(&self, _: &mut Formatter) -> fmt::Result {
+        Ok(())
+    }
+}
@@ -0,0 +1,64 @@
+use std::fmt::Display;
+use std::fmt::{Formatter, self};
+
+/// Escapes the html special characters when displayed, so that it is safe to be used both as a
+/// text content and as a quoted attribute value.
+pub struct Escaped<'a>(pub &'a str);
+
+impl<'a> Display for Escaped<'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let mut last = 0;
+        for (i, ch) in self.0.char_indices() {
+            let escaped = match ch {
+                '&' => "&amp;",
+                '<' => "&lt;",
+                '>' => "&gt;",
+                '"' => "&quot;",
+                '\'' => "&#39;",
+                _ => continue
+            };
+            f.write_str(&self.0[last..i])?;
+            f.write_str(escaped)?;
+            last = i + 1;
+        }
+        f.write_str(&self.0[last..])
+    }
+}
+
+/// Decodes the character references in an html text, the way the browser parses it. Only the
+/// numeric references and the named ones written by `Escaped` are known, any other is kept as is.
+pub fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest.find(';')
+            .and_then(|end| decode(&rest[1..end]).map(|ch| (ch, end)));
+        if let Some((ch, end)) = decoded {
+            unescaped.push(ch);
+            rest = &rest[end + 1..];
+        } else {
+            unescaped.push('&');
+            rest = &rest[1..];
+        }
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+fn decode(reference: &str) -> Option<char> {
+    match reference {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some('\u{a0}'),
+        _ if reference.starts_with("#x") || reference.starts_with("#X") => {
+            u32::from_str_radix(&reference[2..], 16).ok().and_then(char::from_u32)
+        }
+        _ if reference.starts_with('#') => reference[1..].parse().ok().and_then(char::from_u32),
+        _ => None
+    }
+}
@@ -1,112 +1,27 @@
-use stdweb::web::{Element, EventListenerHandle, IEventTarget};
-use stdweb::web::event::*;
-use stdweb::unstable::TryInto;
-use std::marker::PhantomData;
 use std::fmt::Debug;
 use std::fmt::{Formatter, self};
 use std::sync::mpsc::Sender;
 use std::sync::mpsc::Receiver;
 use std::sync::mpsc::channel;
-use stdweb::Reference;
+use std::rc::Rc;
+use std::cell::RefCell;
+#[cfg(target_arch = "wasm32")]
+use stdweb::web::Element;
+
+#[cfg(target_arch = "wasm32")]
+pub use self::wasm::DOMEventListener;
 
 /// Add or remove events from the DOM
 pub trait DOMEvent {
     fn event_type(&self) -> &'static str;
 
+    #[cfg(target_arch = "wasm32")]
     fn attach(&mut self, parent: &Element);
 
+    #[cfg(target_arch = "wasm32")]
     fn detach(&mut self);
 }
 
-/// A wrapper construct to encapsulate all events
-pub struct DOMEventListener<T, F> where
-    F: FnMut(T) + 'static,
-    T: ConcreteEvent {
-    event_type: &'static str,
-    listener: Option<F>,
-    listener_handle: Option<EventListenerHandle>,
-    _phantom: PhantomData<T>,
-}
-
-impl<T, F> DOMEventListener<T, F> where
-    F: FnMut(T) + 'static,
-    T: ConcreteEvent {
-    pub fn new(listener: F) -> DOMEventListener<T, F> {
-        DOMEventListener {
-            event_type: T::EVENT_TYPE,
-            listener: Some(listener),
-            listener_handle: None,
-            _phantom: PhantomData,
-        }
-    }
-}
-
-impl<T, F> DOMEvent for DOMEventListener<T, F> where
-    F: FnMut(T) + 'static,
-    T: ConcreteEvent {
-    fn event_type(&self) -> &'static str {
-        self.event_type
-    }
-
-    fn attach(&mut self, parent: &Element) {
-        let listener = self.listener.take()
-            .expect("Event listener is either already attached or detached");
-        let listener_handle = parent.add_event_listener(listener);
-        self.listener_handle = Some(listener_handle);
-    }
-
-    fn detach(&mut self) {
-        let listener_handle = self.listener_handle.take()
-            .expect("Event must be attached for it to detach");
-        listener_handle.remove();
-    }
-}
-
-macro_rules! convert_to_dom_ev_listener {
-    ($( $listener:ty ),*) => {
-        $(
-            impl<F> From<F> for DOMEventListener<$listener, F> where
-                F: FnMut($listener) {
-                fn from(item: F) -> Self {
-                    DOMEventListener::new(item)
-                }
-            }
-        )*
-    };
-}
-
-convert_to_dom_ev_listener!(
-    ClickEvent,
-    DoubleClickEvent,
-    MouseDownEvent,
-    MouseUpEvent,
-    MouseMoveEvent,
-    KeyPressEvent,
-    KeyDownEvent,
-    KeyUpEvent,
-    ProgressEvent,
-    LoadStartEvent,
-    LoadEndEvent,
-    ProgressLoadEvent,
-    ProgressAbortEvent,
-    ProgressErrorEvent,
-    SocketCloseEvent,
-    SocketErrorEvent,
-    SocketOpenEvent,
-    SocketMessageEvent,
-    HashChangeEvent,
-    PopStateEvent,
-    ChangeEvent,
-    ResourceLoadEvent,
-    ResourceAbortEvent,
-    ResourceErrorEvent,
-    ResizeEvent,
-    InputEvent,
-    ReadyStateChangeEvent,
-    FocusEvent,
-    BlurEvent
-);
-
 impl Debug for DOMEvent {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         write!(f, "EventType = \"{}\"", self.event_type())
@@ -126,27 +41,23 @@ impl Eq for DOMEvent {}
 pub struct RenderRequest {
     tx: Sender<bool>,
     rx: Receiver<bool>,
-    callback_ref: Reference
+    on_send: Rc<Fn()>
 }
 
 impl RenderRequest {
     pub fn new<T: Fn() + 'static>(on_send: T) -> RenderRequest {
         let (tx, rx) = channel();
-        let callback_ref = js! {
-            var callback = @{on_send};
-            return callback;
-        }.try_into().unwrap();
         RenderRequest {
             rx,
             tx,
-            callback_ref
+            on_send: Rc::new(on_send)
         }
     }
 
     pub fn sender(&self) -> RenderRequestSender {
         RenderRequestSender {
             tx: self.tx.clone(),
-            callback_ref: self.callback_ref.clone()
+            on_send: self.on_send.clone()
         }
     }
 
@@ -159,15 +70,167 @@ impl RenderRequest {
 #[derive(Clone)]
 pub struct RenderRequestSender {
     tx: Sender<bool>,
-    callback_ref: Reference
+    on_send: Rc<Fn()>
 }
 
 impl RenderRequestSender {
     pub fn send(&self) {
         self.tx.send(true)
             .expect("The receiver of the app is not present which is impossible.");
-        js!{ @(no_return)
-            @{&self.callback_ref}();
+        (self.on_send)();
+    }
+}
+
+/// An event emitted by a component to its parent. The parent binds its listener onto the
+/// callback of the child component, which is then rebound on every render of the parent. Emitting
+/// an unbound callback does nothing.
+pub struct Callback<T>(Rc<RefCell<Option<Listener<T>>>>);
+
+type Listener<T> = Rc<Fn(T)>;
+
+impl<T> Callback<T> {
+    pub fn new() -> Callback<T> {
+        Callback(Rc::new(RefCell::new(None)))
+    }
+
+    pub fn emit(&self, value: T) {
+        let listener = self.0.borrow().clone();
+        if let Some(listener) = listener {
+            listener(value);
+        }
+    }
+
+    /// Binds the listener of the `other` callback onto this one.
+    pub fn bind(&self, other: &Callback<T>) {
+        *self.0.borrow_mut() = other.0.borrow().clone();
+    }
+
+    pub fn unbind(&self) {
+        *self.0.borrow_mut() = None;
+    }
+
+    pub fn is_bound(&self) -> bool {
+        self.0.borrow().is_some()
+    }
+}
+
+impl<T> Clone for Callback<T> {
+    fn clone(&self) -> Self {
+        Callback(self.0.clone())
+    }
+}
+
+impl<T> Default for Callback<T> {
+    fn default() -> Self {
+        Callback::new()
+    }
+}
+
+impl<T> Debug for Callback<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Callback {{ bound: {} }}", self.is_bound())
+    }
+}
+
+impl<T, F: Fn(T) + 'static> From<F> for Callback<T> {
+    fn from(item: F) -> Self {
+        Callback(Rc::new(RefCell::new(Some(Rc::new(item)))))
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod wasm {
+    use stdweb::web::{Element, EventListenerHandle, IEventTarget};
+    use stdweb::web::event::*;
+    use std::marker::PhantomData;
+    use super::DOMEvent;
+
+    /// A wrapper construct to encapsulate all events
+    pub struct DOMEventListener<T, F> where
+        F: FnMut(T) + 'static,
+        T: ConcreteEvent {
+        event_type: &'static str,
+        listener: Option<F>,
+        listener_handle: Option<EventListenerHandle>,
+        _phantom: PhantomData<T>,
+    }
+
+    impl<T, F> DOMEventListener<T, F> where
+        F: FnMut(T) + 'static,
+        T: ConcreteEvent {
+        pub fn new(listener: F) -> DOMEventListener<T, F> {
+            DOMEventListener {
+                event_type: T::EVENT_TYPE,
+                listener: Some(listener),
+                listener_handle: None,
+                _phantom: PhantomData,
+            }
+        }
+    }
+
+    impl<T, F> DOMEvent for DOMEventListener<T, F> where
+        F: FnMut(T) + 'static,
+        T: ConcreteEvent {
+        fn event_type(&self) -> &'static str {
+            self.event_type
+        }
+
+        fn attach(&mut self, parent: &Element) {
+            let listener = self.listener.take()
+                .expect("Event listener is either already attached or detached");
+            let listener_handle = parent.add_event_listener(listener);
+            self.listener_handle = Some(listener_handle);
+        }
+
+        fn detach(&mut self) {
+            let listener_handle = self.listener_handle.take()
+                .expect("Event must be attached for it to detach");
+            listener_handle.remove();
         }
     }
-}
\ No newline at end of file
+
+    macro_rules! convert_to_dom_ev_listener {
+        ($( $listener:ty ),*) => {
+            $(
+                impl<F> From<F> for DOMEventListener<$listener, F> where
+                    F: FnMut($listener) {
+                    fn from(item: F) -> Self {
+                        DOMEventListener::new(item)
+                    }
+                }
+            )*
+        };
+    }
+
+    convert_to_dom_ev_listener!(
+        ClickEvent,
+        DoubleClickEvent,
+        MouseDownEvent,
+        MouseUpEvent,
+        MouseMoveEvent,
+        KeyPressEvent,
+        KeyDownEvent,
+        KeyUpEvent,
+        ProgressEvent,
+        LoadStartEvent,
+        LoadEndEvent,
+        ProgressLoadEvent,
+        ProgressAbortEvent,
+        ProgressErrorEvent,
+        SocketCloseEvent,
+        SocketErrorEvent,
+        SocketOpenEvent,
+        SocketMessageEvent,
+        HashChangeEvent,
+        PopStateEvent,
+        ChangeEvent,
+        ResourceLoadEvent,
+        ResourceAbortEvent,
+        ResourceErrorEvent,
+        ResizeEvent,
+        InputEvent,
+        ReadyStateChangeEvent,
+        FocusEvent,
+        BlurEvent
+    );
+}
@@ -0,0 +1,93 @@
+use std::cell::RefCell;
+use std::mem;
+use indexmap::IndexMap;
+use velement::{VElement, AttrValue};
+use vnode::VNode;
+use vtext::VText;
+use vraw::VRaw;
+use validate;
+use CowStr;
+
+pub type Head = IndexMap<CowStr, VElement>;
+
+/// The head being collected, along with whether it is already written by a streamed document.
+struct Collected {
+    head: Head,
+    is_written: bool,
+}
+
+thread_local! {
+    static HEAD: RefCell<Option<Collected>> = const { RefCell::new(None) };
+}
+
+/// Adds the `entry` to the head of the `Document` being rendered on the server. It is ignored
+/// otherwise, e.g. on the client. It replaces the entry added earlier with the same `key` in its
+/// place, so that a nested component may override the ones of its ancestors.
+///
+/// A streamed document writes its head once the app starts to be written, so an entry added by a
+/// component rendered after that is left out with a warning.
+pub fn add<K, E>(key: K, entry: E) where
+    K: Into<CowStr>,
+    E: Into<VElement> {
+    HEAD.with(|it| {
+        if let Some(ref mut collected) = *it.borrow_mut() {
+            let key = key.into();
+            if collected.is_written {
+                validate::warn(&format!("The head entry `{}` is added after the head is streamed", key));
+            } else {
+                collected.head.insert(key, entry.into());
+            }
+        }
+    });
+}
+
+pub fn title<T: Into<CowStr>>(title: T) {
+    add("title", ("title", VNode::Text(VText::from(title))));
+}
+
+pub fn meta<K, B, C>(key: K, attrs: Vec<(B, C)>) where
+    K: Into<CowStr>,
+    B: Into<CowStr>,
+    C: Into<AttrValue> {
+    add(key, ("meta", attrs));
+}
+
+pub fn link<K, B, C>(key: K, attrs: Vec<(B, C)>) where
+    K: Into<CowStr>,
+    B: Into<CowStr>,
+    C: Into<AttrValue> {
+    add(key, ("link", attrs));
+}
+
+/// Adds an inline `<script>`. The `code` is not escaped, except for a `</` which would otherwise
+/// close the script early.
+pub fn script<K, T>(key: K, code: T) where
+    K: Into<CowStr>,
+    T: Into<CowStr> {
+    let code = code.into().replace("</", "<\\/");
+    add(key, ("script", VNode::Raw(VRaw::from(code))));
+}
+
+/// Runs `f` while collecting the entries added by it on top of the `head`.
+pub fn collect<F: FnOnce()>(head: &mut Head, f: F) {
+    let collected = Collected {
+        head: mem::take(head),
+        is_written: false,
+    };
+    let outer = HEAD.with(|it| it.borrow_mut().replace(collected));
+    f();
+    let collected = HEAD.with(|it| mem::replace(&mut *it.borrow_mut(), outer))
+        .expect("Impossible. The head was set above");
+    *head = collected.head;
+}
+
+/// Writes the entries collected so far with `write`, after which the entries added are left out.
+pub fn write_collected<F, R>(write: F) -> R where
+    F: FnOnce(&Head) -> R {
+    HEAD.with(|it| {
+        let mut collected = it.borrow_mut();
+        let collected = collected.as_mut().expect("The head is only written while it is collected");
+        collected.is_written = true;
+        write(&collected.head)
+    })
+}
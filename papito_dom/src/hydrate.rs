@@ -0,0 +1,193 @@
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::fmt::{Formatter, self};
+use std::rc::Rc;
+use std::cell::RefCell;
+use backend::Backend;
+use events::RenderRequestSender;
+use validate;
+use vnode::VNode;
+use state::{self, SavedStates};
+
+/// A difference between the markup rendered by the server and the first render on the client.
+/// The DOM is patched to the client render wherever they differ.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Mismatch {
+    /// A different node was found in place of the expected one.
+    Node { expected: String, found: String },
+    /// A text node was found with a different content.
+    Text { expected: String, found: String },
+    /// No node was rendered by the server for the expected one.
+    Missing(String),
+    /// A node was rendered by the server which is not rendered on the client.
+    Extra(String),
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            Mismatch::Node { ref expected, ref found } => write!(f, "Expected {} but found {} in the server rendered markup", expected, found),
+            Mismatch::Text { ref expected, ref found } => write!(f, "Expected the text {:?} but found {:?} in the server rendered markup", expected, found),
+            Mismatch::Missing(ref expected) => write!(f, "Expected {} but it is missing in the server rendered markup", expected),
+            Mismatch::Extra(ref found) => write!(f, "Found {} in the server rendered markup which is not rendered on the client", found),
+        }
+    }
+}
+
+/// Required to adopt the nodes already rendered by the server instead of creating them anew.
+pub trait DOMHydrate {
+    fn hydrate<B: Backend>(self, backend: &B, hydration: &mut Hydration<B>, render_req: RenderRequestSender) -> Self;
+}
+
+/// A cursor over the children of a `parent` rendered by the server. The nodes are adopted in
+/// order, along with the mismatches shared with the cursors of the descendants. The position of
+/// the vnode being hydrated is tracked to find the state saved by the server for a component.
+pub struct Hydration<B: Backend> {
+    parent: B::Node,
+    nodes: VecDeque<B::Node>,
+    mismatches: Rc<RefCell<Vec<Mismatch>>>,
+    path: Rc<RefCell<Vec<usize>>>,
+    states: Rc<SavedStates>,
+}
+
+impl<B: Backend> Hydration<B> {
+    pub fn new(backend: &B, parent: &B::Node, states: SavedStates) -> Hydration<B> {
+        Hydration {
+            parent: parent.clone(),
+            nodes: backend.child_nodes(parent).into_iter().collect(),
+            mismatches: Rc::new(RefCell::new(vec![])),
+            path: Rc::new(RefCell::new(vec![0])),
+            states: Rc::new(states),
+        }
+    }
+
+    /// A cursor over the children of the adopted `node`.
+    pub fn children_of(&self, backend: &B, node: &B::Node) -> Hydration<B> {
+        Hydration {
+            parent: node.clone(),
+            nodes: backend.child_nodes(node).into_iter().collect(),
+            mismatches: self.mismatches.clone(),
+            path: self.path.clone(),
+            states: self.states.clone(),
+        }
+    }
+
+    /// Enters the child at the `index` of the vnode being hydrated.
+    pub fn enter(&self, index: usize) {
+        self.path.borrow_mut().push(index);
+    }
+
+    pub fn leave(&self) {
+        self.path.borrow_mut().pop();
+    }
+
+    /// The state saved by the server for the component being hydrated.
+    pub fn saved_state(&self) -> Option<String> {
+        self.states.get(&state::position_key(&self.path.borrow())).cloned()
+    }
+
+    pub fn parent(&self) -> &B::Node {
+        &self.parent
+    }
+
+    /// The next node to be adopted. The ones created on the client are inserted before it.
+    pub fn peek(&self) -> Option<&B::Node> {
+        self.nodes.front()
+    }
+
+    /// Adopts the next node.
+    pub fn adopt_next(&mut self) -> Option<B::Node> {
+        self.nodes.pop_front()
+    }
+
+    /// Adopts the next node if it is an element with the `tag`.
+    pub fn next_element(&mut self, backend: &B, tag: &str) -> Option<B::Node> {
+        let is_same_tag = self.peek()
+            .and_then(|node| backend.tag_name(node))
+            .map(|it| it.eq_ignore_ascii_case(tag))
+            .unwrap_or(false);
+        if is_same_tag {
+            self.adopt_next()
+        } else {
+            None
+        }
+    }
+
+    /// Adopts the next node if it is a text node, with its content set to `content`. The adjacent
+    /// texts rendered by the server are parsed into a single node, so it is split when it only
+    /// starts with the `content`. An empty text is never rendered by the server.
+    pub fn next_text(&mut self, backend: &B, content: &str) -> Option<B::Node> {
+        if content.is_empty() {
+            return None;
+        }
+        let found = self.peek().and_then(|node| backend.text(node))?;
+        let node = self.adopt_next().unwrap();
+        if found.len() > content.len() && found.starts_with(content) {
+            self.split_text(backend, &node, &found, content);
+        } else if found != content {
+            self.report(Mismatch::Text {
+                expected: content.to_string(),
+                found,
+            });
+            backend.set_text_content(&node, content);
+        }
+        Some(node)
+    }
+
+    /// Splits the adopted text `node` after its own `content`, as the rest of the `found` text
+    /// belongs to the next nodes. The rest is the next node to be adopted.
+    pub fn split_text(&mut self, backend: &B, node: &B::Node, found: &str, content: &str) {
+        backend.set_text_content(node, content);
+        let rest = backend.create_text(&found[content.len()..]);
+        backend.insert(&self.parent, &rest, self.peek());
+        self.nodes.push_front(rest);
+    }
+
+    /// Gives way to a node which could not be adopted. The next node is removed, if any, as it is
+    /// rendered differently on the client.
+    pub fn mismatched(&mut self, backend: &B, expected: String) {
+        if let Some(found) = self.adopt_next() {
+            self.report(Mismatch::Node {
+                expected,
+                found: describe(backend, &found),
+            });
+            backend.remove_child(&self.parent, &found);
+        } else {
+            self.report(Mismatch::Missing(expected));
+        }
+    }
+
+    pub fn report(&self, mismatch: Mismatch) {
+        validate::warn(&mismatch.to_string());
+        self.mismatches.borrow_mut().push(mismatch);
+    }
+
+    /// Removes the nodes which were not adopted and gives all the mismatches found so far.
+    pub fn finish(mut self, backend: &B) -> Vec<Mismatch> {
+        while let Some(extra) = self.adopt_next() {
+            self.report(Mismatch::Extra(describe(backend, &extra)));
+            backend.remove_child(&self.parent, &extra);
+        }
+        self.mismatches.borrow().clone()
+    }
+}
+
+fn describe<B: Backend>(backend: &B, node: &B::Node) -> String {
+    if let Some(tag) = backend.tag_name(node) {
+        format!("<{}>", tag)
+    } else if let Some(text) = backend.text(node) {
+        format!("the text {:?}", text)
+    } else {
+        "a node".to_string()
+    }
+}
+
+/// Hydrates the children of the `parent` rendered by the server with the `vnode`, as its first
+/// render. The components are created with the `states` saved by the server. Gives the mismatches
+/// found, which are already patched to the client render.
+pub fn hydrate<B: Backend>(vnode: VNode, backend: &B, parent: &B::Node, states: SavedStates, render_req: RenderRequestSender) -> (VNode, Vec<Mismatch>) {
+    let mut hydration = Hydration::new(backend, parent, states);
+    let vnode = vnode.hydrate(backend, &mut hydration, render_req);
+    let mismatches = hydration.finish(backend);
+    (vnode, mismatches)
+}
@@ -2,6 +2,10 @@ extern crate indexmap;
 #[cfg(target_arch = "wasm32")]
 #[macro_use]
 extern crate stdweb;
+#[cfg(feature = "save_state")]
+extern crate serde;
+#[cfg(feature = "save_state")]
+extern crate serde_json;
 
 use std::borrow::Cow;
 use vnode::VNode;
@@ -11,6 +15,7 @@ use vlist::VList;
 #[cfg(target_arch = "wasm32")]
 use stdweb::web::event::ConcreteEvent;
 use vcomponent::VComponent;
+use vraw::VRaw;
 
 type CowStr = Cow<'static, str>;
 
@@ -19,22 +24,51 @@ mod vtext;
 mod velement;
 mod vlist;
 mod vcomponent;
-#[cfg(target_arch = "wasm32")]
+mod vraw;
 mod vdiff;
-#[cfg(target_arch = "wasm32")]
 mod events;
 mod traits;
+mod backend;
+mod escape;
+mod namespace;
+mod validate;
+mod context;
+mod computed;
+mod hydrate;
+mod state;
+#[cfg(not(target_arch = "wasm32"))]
+mod load;
+pub mod head;
+#[cfg(not(target_arch = "wasm32"))]
+mod document;
+pub mod testing;
 
-#[cfg(target_arch = "wasm32")]
 pub use traits::DOMRender;
 #[cfg(target_arch = "wasm32")]
-pub use events::{DOMEventListener, RenderRequest};
-pub use traits::{Render, Component, Lifecycle};
+pub use events::DOMEventListener;
+pub use events::{DOMEvent, RenderRequest, RenderRequestSender, Callback};
+pub use backend::Backend;
+#[cfg(target_arch = "wasm32")]
+pub use backend::StdwebBackend;
+pub use traits::{Render, Component, Lifecycle, Load};
+#[cfg(not(target_arch = "wasm32"))]
+pub use load::{ServerRenderFuture, block_on};
+pub use velement::{AttrValue, ClassList, Style};
+pub use namespace::Namespace;
+pub use context::{Context, provide, consume};
+pub use computed::ComputedCache;
+pub use hydrate::{DOMHydrate, Hydration, Mismatch, hydrate};
+pub use state::{SavedStates, restored_state};
+#[cfg(feature = "save_state")]
+pub use state::{save_state, restore_state};
+#[cfg(not(target_arch = "wasm32"))]
+pub use document::Document;
+pub use validate::{ValidationError, is_known_html_tag, set_strict_mode, set_warning_handler};
 
 pub mod prelude {
     pub use vnode::VNode;
     #[cfg(not(target_arch = "wasm32"))]
-    pub use traits::RenderToString;
+    pub use traits::{RenderToString, RenderToStringAsync, RenderToWriter};
 }
 
 pub fn comp<C: Component + 'static>(props: C::Props) -> VComponent {
@@ -53,10 +87,129 @@ pub fn li<T: Into<VList>>(li: T) -> VList {
     li.into()
 }
 
+pub fn attr<T: Into<AttrValue>>(value: T) -> AttrValue {
+    value.into()
+}
+
+pub fn raw<T: Into<VRaw>>(raw: T) -> VRaw {
+    raw.into()
+}
+
 pub fn h<T: Into<VNode>>(node_like: T) -> VNode {
     node_like.into()
 }
 
+/// A value interpolated within a template. The strings become text, while the vnodes are used
+/// as is.
+pub trait Interpolate {
+    fn interpolate(self) -> VNode;
+}
+
+impl<T: Into<CowStr>> Interpolate for T {
+    fn interpolate(self) -> VNode {
+        h(txt(self))
+    }
+}
+
+macro_rules! impl_interpolate_for_vnode {
+    ($( $vnode:ty ),*) => {
+        $(
+            impl Interpolate for $vnode {
+                fn interpolate(self) -> VNode {
+                    h(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_interpolate_for_vnode!(VNode, VText, VElement, VList, VComponent, VRaw);
+
+pub fn interpolate<T: Interpolate>(value: T) -> VNode {
+    value.interpolate()
+}
+
+/// A value given to a prop. The optional props generated by `#[component]` are `Option`s, so the
+/// value is wrapped in `Some`, and the required ones are `Required`, while the hand-written props
+/// are used as is.
+pub trait IntoProp<P> {
+    fn into_prop(self) -> P;
+}
+
+impl<T> IntoProp<T> for T {
+    fn into_prop(self) -> T {
+        self
+    }
+}
+
+impl<T> IntoProp<Option<T>> for T {
+    fn into_prop(self) -> Option<T> {
+        Some(self)
+    }
+}
+
+impl<T> IntoProp<Required<T>> for T {
+    fn into_prop(self) -> Required<T> {
+        Required(Some(self))
+    }
+}
+
+pub fn prop<T: IntoProp<P>, P>(value: T) -> P {
+    value.into_prop()
+}
+
+/// A required prop of the props generated by `#[component]`. It is only missing when the props
+/// are partially constructed with `..`, which fails to compile with `has_required_props`.
+pub struct Required<T>(Option<T>);
+
+impl<T> Required<T> {
+    pub fn get(&self) -> &T {
+        self.0.as_ref().expect("The required prop is not given")
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0.expect("The required prop is not given")
+    }
+}
+
+impl<T> Default for Required<T> {
+    fn default() -> Self {
+        Required(None)
+    }
+}
+
+/// Whether all of the `required` props are `given`, which is checked as a constant so that leaving
+/// one out fails to compile.
+pub const fn has_required_props(required: &[&str], given: &[&str]) -> bool {
+    let mut i = 0;
+    while i < required.len() {
+        let mut j = 0;
+        while j < given.len() && !str_eq(required[i], given[j]) {
+            j += 1;
+        }
+        if j == given.len() {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+const fn str_eq(lhs: &str, rhs: &str) -> bool {
+    let (lhs, rhs) = (lhs.as_bytes(), rhs.as_bytes());
+    if lhs.len() != rhs.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < lhs.len() {
+        if lhs[i] != rhs[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
 #[cfg(target_arch = "wasm32")]
 pub fn ev<E, T, F>(listener: E) -> Box<events::DOMEvent> where
     E: Into<events::DOMEventListener<T, F>>,
@@ -65,22 +218,100 @@ pub fn ev<E, T, F>(listener: E) -> Box<events::DOMEvent> where
     Box::new(listener.into())
 }
 
+/// Creates a vnode out of the hyperscript-like arguments.
+///
+/// Panics on an element with an invalid tag or attribute name, a void element with children or a
+/// `script` whose text contains `</script`, just like `VElement::new`. Use `VElement::try_new` to
+/// handle them instead.
 #[macro_export]
 macro_rules! h {
     // Creates a component vnode with map as props where props is a struct
     (comp $t:ty, { $( $k:ident => $v:expr ),* } $(,)*) => {{
         type T = <$t as $crate::Component>::Props;
         $crate::h($crate::comp::<$t>(T {
-            $( $k: $v ),*
+            $( $k: $crate::prop($v) ),*
+        }))
+    }};
+    // Creates a component vnode with only a few of the props, the rest being their defaults. The
+    // props need to be `Default`, as the `#[component]` ones are, and its required props given.
+    (comp $t:ty, { $( $k:ident => $v:expr, )* .. } $(,)*) => {{
+        type T = <$t as $crate::Component>::Props;
+        const _: () = if !$crate::has_required_props(
+            <$t as $crate::Component>::REQUIRED_PROPS, &[ $( stringify!($k) ),* ]) {
+            panic!("a required prop is left out with `..`");
+        };
+        $crate::h($crate::comp::<$t>(T {
+            $( $k: $crate::prop($v), )*
+            ..::std::default::Default::default()
         }))
     }};
+    // Creates a component vnode with map as props and listeners bound to its emitted events
+    (comp $t:ty, { $( $k:ident => $v:expr ),* $(,)* }, [ $( $e:ident => $l:expr ),* $(,)* ] $(,)*) => {{
+        type T = <$t as $crate::Component>::Props;
+        let mut comp = $crate::comp::<$t>(T {
+            $( $k: $crate::prop($v) ),*
+        });
+        $(
+            let listener = $crate::Callback::from($l);
+            comp.add_binding(move |instance: &$t, bind| {
+                if bind {
+                    instance.$e().bind(&listener);
+                } else {
+                    instance.$e().unbind();
+                }
+            });
+        )*
+        $crate::h(comp)
+    }};
+    // Creates a component vnode with a few of the props and listeners bound to its emitted events
+    (comp $t:ty, { $( $k:ident => $v:expr, )* .. }, [ $( $e:ident => $l:expr ),* $(,)* ] $(,)*) => {{
+        type T = <$t as $crate::Component>::Props;
+        const _: () = if !$crate::has_required_props(
+            <$t as $crate::Component>::REQUIRED_PROPS, &[ $( stringify!($k) ),* ]) {
+            panic!("a required prop is left out with `..`");
+        };
+        let mut comp = $crate::comp::<$t>(T {
+            $( $k: $crate::prop($v), )*
+            ..::std::default::Default::default()
+        });
+        $(
+            let listener = $crate::Callback::from($l);
+            comp.add_binding(move |instance: &$t, bind| {
+                if bind {
+                    instance.$e().bind(&listener);
+                } else {
+                    instance.$e().unbind();
+                }
+            });
+        )*
+        $crate::h(comp)
+    }};
+    // Creates a component vnode with no props and listeners bound to its emitted events
+    (comp $t:ty, [ $( $e:ident => $l:expr ),* $(,)* ] $(,)*) => {{
+        let mut comp = $crate::comp::<$t>(());
+        $(
+            let listener = $crate::Callback::from($l);
+            comp.add_binding(move |instance: &$t, bind| {
+                if bind {
+                    instance.$e().bind(&listener);
+                } else {
+                    instance.$e().unbind();
+                }
+            });
+        )*
+        $crate::h(comp)
+    }};
     // Creates a component vnode with no props
     (comp $t:ty) => {
         $crate::h($crate::comp::<$t>(()))
     };
+    // Creates a raw html vnode from trusted html
+    (raw $n:expr) => {
+        $crate::h($crate::raw($n))
+    };
     // Creates vnodes from a vec
     (vec $n:expr) => {
-        $crate::h($crate::li($n));
+        $crate::h($crate::li($n))
     };
     // Creates keyed vnodes
     ({ $( $k:expr => $v:expr ),* $(,)* }) => {
@@ -100,10 +331,11 @@ macro_rules! h {
     };
     // Creates an element with map based attributes
     ($n:expr, { $($k:expr => $v:expr),* $(,)* } $(,)*) => {
-        $crate::h($crate::el(($n, vec![ $( ($k, $v) ),* ])))
+        $crate::h($crate::el(($n, vec![ $( ($k, $crate::attr($v)) ),* ])))
     };
     // Creates an element with event handlers
     ($n:expr, [ $( $ev:expr ),* $(,)* ] $(,)*) => {{
+        #[allow(unused_mut)]
         let mut el = $crate::el(($n, ()));
         #[cfg(target_arch = "wasm32")]
         el.set_events(vec![ $( $crate::ev( $ev ) ),* ]);
@@ -111,24 +343,27 @@ macro_rules! h {
     }};
     // Creates an element with map based attributes and event handlers
     ($n:expr, { $($k:expr => $v:expr),* $(,)* }, [ $( $ev:expr ),* $(,)* ] $(,)*) => {{
-        let mut el = $crate::el(($n, vec![ $( ($k, $v) ),* ]));
+        #[allow(unused_mut)]
+        let mut el = $crate::el(($n, vec![ $( ($k, $crate::attr($v)) ),* ]));
         #[cfg(target_arch = "wasm32")]
         el.set_events(vec![ $( $crate::ev( $ev ) ),* ]);
         $crate::h(el)
     }};
-    // Creates an element with map based attributes, event handlers and other arguments
+    // Creates an element with map based attributes, event handlers and children
     ($n:expr, { $($k:expr => $v:expr),* $(,)* }, [ $( $ev:expr ),* $(,)* ], $( $o:expr ),* $(,)*) => {{
-        let mut el = $crate::el(($n, vec![ $( ($k, $v) ),* ], $( $o ),*));
+        #[allow(unused_mut)]
+        let mut el = $crate::el(($n, vec![ $( ($k, $crate::attr($v)) ),* ], ($( $o ),*)));
         #[cfg(target_arch = "wasm32")]
         el.set_events(vec![ $( $crate::ev( $ev ) ),* ]);
         $crate::h(el)
     }};
-    // Creates an element with map based attributes along with other arguments
+    // Creates an element with map based attributes along with children
     ($n:expr, { $($k:expr => $v:expr),* $(,)* }, $( $o:expr ),* $(,)*) => {
-        $crate::h($crate::el(($n, vec![ $( ($k, $v) ),* ], $( $o ),*)))
+        $crate::h($crate::el(($n, vec![ $( ($k, $crate::attr($v)) ),* ], ($( $o ),*))))
     };
     // Creates an element with plain arguments, except attributes (not strictly), and event handlers
     ($n:expr, [ $( $ev:expr ),* $(,)* ], $( $m:expr ),* $(,)*) => {{
+        #[allow(unused_mut)]
         let mut el = $crate::el(($n, $( $m ),*));
         #[cfg(target_arch = "wasm32")]
         el.set_events(vec![ $( $crate::ev( $ev ) ),* ]);
@@ -136,6 +371,7 @@ macro_rules! h {
     }};
     // Creates an element with plain arguments and event handlers
     ($n:expr, $s:expr, [ $( $ev:expr ),* $(,)* ], $( $m:expr ),* $(,)*) => {{
+        #[allow(unused_mut)]
         let mut el = $crate::el(($n, $s, $( $m ),*));
         #[cfg(target_arch = "wasm32")]
         el.set_events(vec![ $( $crate::ev( $ev ) ),* ]);
@@ -147,6 +383,36 @@ macro_rules! h {
     };
 }
 
+/// Creates a `ClassList`. A class followed by `=> condition` is only added when the condition holds.
+///
+/// `h!("button", { "class" => classes!("btn", "active" => is_active) })`
+#[macro_export]
+macro_rules! classes {
+    ($( $c:expr $( => $cond:expr )* ),* $(,)*) => {{
+        let mut class_list = $crate::ClassList::new();
+        $(
+            if true $( && $cond )* {
+                class_list.add($c);
+            }
+        )*
+        class_list
+    }};
+}
+
+/// Creates a `Style` out of the `property => value` declarations.
+///
+/// `h!("div", { "style" => style!("color" => "red") })`
+#[macro_export]
+macro_rules! style {
+    ($( $k:expr => $v:expr ),* $(,)*) => {{
+        let mut style = $crate::Style::new();
+        $(
+            style.set($k, $v);
+        )*
+        style
+    }};
+}
+
 #[cfg(test)]
 mod test {
     use vtext::VText;
@@ -155,10 +421,30 @@ mod test {
     use std::borrow::Cow;
     #[cfg(target_arch = "wasm32")]
     use stdweb::web::event::InputEvent;
-    use traits::{Component, Lifecycle, Render, RenderToString};
+    use traits::{Component, Lifecycle, Render, RenderToString, RenderToStringAsync, RenderToWriter, DOMRender, ServerRender, CollectStates, Load};
     use vcomponent::VComponent;
+    use vraw::VRaw;
+    use velement::AttrValue;
+    use namespace::Namespace;
+    use validate::{ValidationError, is_known_html_tag, set_strict_mode, set_warning_handler};
+    use vdiff::DOMPatch;
+    use events::{RenderRequest, Callback};
+    use context::{self, Context};
+    use computed::ComputedCache;
+    use testing::{MemoryDocument, MemoryNode, Operation};
+    use hydrate::{hydrate, Mismatch};
+    use state::{SavedStates, restored_state};
+    use document::Document;
+    use head;
+    use backend::Backend;
     use std::rc::Rc;
-    use std::cell::RefCell;
+    use std::cell::{Cell, RefCell};
+    use std::io::{self, Write};
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::task::{Context as TaskContext, Poll, Wake, Waker};
+    use load::block_on;
 
     #[test]
     fn should_create_text_vnode() {
@@ -169,7 +455,7 @@ mod test {
     #[test]
     fn should_create_empty_velement() {
         let node = h!("div", _);
-        assert_eq!(VNode::Element(VElement::new("div".into(), None, None, None, false)), node);
+        assert_eq!(VNode::Element(VElement::new("div".into(), None, None, vec![])), node);
     }
 
     #[test]
@@ -180,26 +466,32 @@ mod test {
                 "span".into(),
                 None,
                 None,
-                Some(VNode::Text(VText::new("Hello World".into()))),
-                false,
+                vec![VNode::Text(VText::new("Hello World".into()))],
             )),
             node
         );
     }
 
     #[test]
-    fn should_create_self_closing_velement() {
-        let node = h!("br", true);
+    fn should_create_void_velement() {
+        let node = h!("br", _);
         assert_eq!(
             VNode::Element(VElement::new(
                 "br".into(),
                 None,
                 None,
-                None,
-                true,
+                vec![],
             )),
             node
         );
+        assert!(VElement::new("BR".into(), None, None, vec![]).is_void());
+        assert!(!VElement::new("div".into(), None, None, vec![]).is_void());
+    }
+
+    #[test]
+    #[should_panic(expected = "The void element `input` cannot have any children")]
+    fn should_not_create_void_velement_with_children() {
+        h!("input", h!("text"));
     }
 
     #[test]
@@ -207,9 +499,9 @@ mod test {
         let node = h!({ "1" => h!("div", _), "2" => h!("div", _), "3" => h!("div", _) });
         assert_eq!(
             VNode::List(vec![
-                (Cow::from("1"), VNode::Element(VElement::new("div".into(), None, None, None, false))),
-                (Cow::from("2"), VNode::Element(VElement::new("div".into(), None, None, None, false))),
-                (Cow::from("3"), VNode::Element(VElement::new("div".into(), None, None, None, false))),
+                (Cow::from("1"), VNode::Element(VElement::new("div".into(), None, None, vec![]))),
+                (Cow::from("2"), VNode::Element(VElement::new("div".into(), None, None, vec![]))),
+                (Cow::from("3"), VNode::Element(VElement::new("div".into(), None, None, vec![]))),
             ].into()),
             node
         );
@@ -220,9 +512,9 @@ mod test {
         let node = h!([h!("div", _), h!("div", _), h!("div", _)]);
         assert_eq!(
             VNode::List(vec![
-                (Cow::from("0"), VNode::Element(VElement::new("div".into(), None, None, None, false))),
-                (Cow::from("1"), VNode::Element(VElement::new("div".into(), None, None, None, false))),
-                (Cow::from("2"), VNode::Element(VElement::new("div".into(), None, None, None, false))),
+                (Cow::from("0"), VNode::Element(VElement::new("div".into(), None, None, vec![]))),
+                (Cow::from("1"), VNode::Element(VElement::new("div".into(), None, None, vec![]))),
+                (Cow::from("2"), VNode::Element(VElement::new("div".into(), None, None, vec![]))),
             ].into()),
             node
         );
@@ -234,9 +526,9 @@ mod test {
         let node = h!(vec list);
         assert_eq!(
             VNode::List(vec![
-                (Cow::from("0"), VNode::Element(VElement::new("div".into(), None, None, None, false))),
-                (Cow::from("1"), VNode::Element(VElement::new("div".into(), None, None, None, false))),
-                (Cow::from("2"), VNode::Element(VElement::new("div".into(), None, None, None, false))),
+                (Cow::from("0"), VNode::Element(VElement::new("div".into(), None, None, vec![]))),
+                (Cow::from("1"), VNode::Element(VElement::new("div".into(), None, None, vec![]))),
+                (Cow::from("2"), VNode::Element(VElement::new("div".into(), None, None, vec![]))),
             ].into()),
             node
         );
@@ -246,7 +538,7 @@ mod test {
     fn should_create_velement_with_class() {
         let node = h!("div", vec![("class", "container")]);
         assert_eq!(
-            VNode::Element(VElement::new("div".into(), Some("container".into()), None, None, false)),
+            VNode::Element(VElement::new("div".into(), Some("container".into()), None, vec![])),
             node
         );
     }
@@ -255,7 +547,7 @@ mod test {
     fn should_create_velement_with_class_with_alt_syntax() {
         let node = h!("div", { "class" => "container" });
         assert_eq!(
-            VNode::Element(VElement::new("div".into(), Some("container".into()), None, None, false)),
+            VNode::Element(VElement::new("div".into(), Some("container".into()), None, vec![])),
             node
         );
     }
@@ -264,7 +556,7 @@ mod test {
     fn should_create_velement_with_attributes() {
         let node = h!("div", { "style" => "background-color: black;" });
         assert_eq!(
-            VNode::Element(VElement::new("div".into(), None, Some(vec![("style", "background-color: black;")].into()), None, false)),
+            VNode::Element(VElement::new("div".into(), None, Some(vec![("style", "background-color: black;")].into()), vec![])),
             node
         );
     }
@@ -277,13 +569,11 @@ mod test {
                 "div".into(),
                 None,
                 None,
-                Some(VNode::Element(VElement::new(
+                vec![VNode::Element(VElement::new(
                     "span".into(),
                     None,
                     None,
-                    None,
-                    false))),
-                false)
+                    vec![]))])
             ),
             node
         );
@@ -301,10 +591,10 @@ mod test {
         ]);
         assert_eq!(
             VNode::List(vec![
-                VNode::Element(VElement::new("div".into(), None, None, None, false)),
+                VNode::Element(VElement::new("div".into(), None, None, vec![])),
                 VNode::Text(VText::new("Hello World".into())),
                 VNode::List(vec![
-                    VNode::Element(VElement::new("div".into(), None, None, None, false)),
+                    VNode::Element(VElement::new("div".into(), None, None, vec![])),
                     VNode::Text(VText::new("Hello World".into()))
                 ].into())
             ].into()),
@@ -316,7 +606,7 @@ mod test {
     fn should_create_empty_input_with_event() {
         let node = h!("input", [ |_: InputEvent| {} ]);
         assert_eq!(
-            VNode::Element(VElement::new("input".into(), None, None, None, false)),
+            VNode::Element(VElement::new("input".into(), None, None, vec![])),
             node
         );
     }
@@ -329,8 +619,7 @@ mod test {
                 "input".into(),
                 None,
                 Some(vec![("disabled", "true")].into()),
-                None,
-                false)
+                vec![])
             ),
             node
         );
@@ -344,8 +633,7 @@ mod test {
                 "div".into(),
                 None,
                 Some(vec![("style", "color: white;")].into()),
-                Some(VNode::Text(VText::new("Hello".into()))),
-                false)
+                vec![VNode::Text(VText::new("Hello".into()))])
             ),
             node
         );
@@ -359,8 +647,7 @@ mod test {
                 "div".into(),
                 None,
                 None,
-                Some(VNode::Text(VText::new("Hello".into()))),
-                false)
+                vec![VNode::Text(VText::new("Hello".into()))])
             ),
             node
         );
@@ -370,37 +657,20 @@ mod test {
     fn should_create_a_component() {
         struct Button;
 
-        impl Lifecycle for Button {}
-        impl Render for Button {
-            fn render(&self) -> VNode {
-                unimplemented!();
-            }
-        }
-
-        impl ComponentOf for Button {
-            type Comp = ButtonComponent;
-        }
-
-        struct ButtonComponent {
-            inner: Rc<RefCell<Button>>
-        }
-
-        impl Component for ButtonComponent {
+        impl Component for Button {
             type Props = ();
 
             fn create(_: (), _: Box<Fn()>) -> Self {
-                ButtonComponent {
-                    inner: Rc::new(RefCell::new(Button))
-                }
+                Button
             }
-            fn update(&mut self, props: Self::Props) {}
-            fn props(&self) -> &Self::Props {
-                &()
+            fn update(&self, _: Self::Props) {}
+            fn eq_props(&self, _: &Self::Props) -> bool {
+                true
             }
         }
 
-        impl Lifecycle for ButtonComponent {}
-        impl Render for ButtonComponent {
+        impl Lifecycle for Button {}
+        impl Render for Button {
             fn render(&self) -> VNode {
                 h!("button", h!("Click"))
             }
@@ -408,7 +678,7 @@ mod test {
 
         let node = h!(comp Button);
         assert_eq!(
-            VNode::Component(VComponent::new::<ButtonComponent>(())),
+            VNode::Component(VComponent::new::<Button>(())),
             node
         );
     }
@@ -420,11 +690,17 @@ mod test {
     }
 
     #[test]
-    fn should_print_html_for_self_closing_br() {
-        let node = h!("br", true);
+    fn should_print_html_for_void_br() {
+        let node = h!("br", _);
         assert_eq!(node.to_string(), "<br>");
     }
 
+    #[test]
+    fn should_print_html_for_void_input_with_attributes() {
+        let node = h!("p", { "class" => "form" }, h!("input", { "type" => "text" }), h!("img", { "src" => "a.png" }));
+        assert_eq!(node.to_string(), r#"<p class="form"><input type="text"><img src="a.png"></p>"#);
+    }
+
     #[test]
     fn should_print_html_for_texted_div() {
         let node = h!("div", h!("Hello World"));
@@ -480,124 +756,1509 @@ mod test {
     fn should_print_html_for_component() {
         struct Button;
 
+        impl Component for Button {
+            type Props = ();
+
+            fn create(_: (), _: Box<Fn()>) -> Self {
+                Button
+            }
+            fn update(&self, _: Self::Props) {}
+            fn eq_props(&self, _: &Self::Props) -> bool {
+                true
+            }
+        }
+
         impl Lifecycle for Button {}
         impl Render for Button {
             fn render(&self) -> VNode {
-                unimplemented!();
+                h!("button", h!("Click"))
             }
         }
 
-        impl ComponentOf for Button {
-            type Comp = ButtonComponent;
+        let mut node = h!(comp Button);
+        assert_eq!(node.render_to_string(), "<button>Click</button>");
+    }
+
+    #[test]
+    fn should_print_html_for_nested_components() {
+        struct Button;
+
+        impl Component for Button {
+            type Props = ();
+
+            fn create(_: (), _: Box<Fn()>) -> Self {
+                Button
+            }
+            fn update(&self, _: Self::Props) {}
+            fn eq_props(&self, _: &Self::Props) -> bool {
+                true
+            }
         }
 
-        struct ButtonComponent {
-            inner: Rc<RefCell<Button>>
+        impl Lifecycle for Button {}
+        impl Render for Button {
+            fn render(&self) -> VNode {
+                h!("button", h!("Click"))
+            }
         }
 
-        impl Component for ButtonComponent {
+        struct Div;
+
+        impl Component for Div {
             type Props = ();
 
             fn create(_: (), _: Box<Fn()>) -> Self {
-                ButtonComponent {
-                    inner: Rc::new(RefCell::new(Button))
-                }
+                Div
             }
-            fn update(&mut self, props: Self::Props) {}
-            fn props(&self) -> &Self::Props {
-                &()
+            fn update(&self, _: Self::Props) {}
+            fn eq_props(&self, _: &Self::Props) -> bool {
+                true
             }
         }
 
-        impl Lifecycle for ButtonComponent {}
-        impl Render for ButtonComponent {
+        impl Lifecycle for Div {}
+        impl Render for Div {
             fn render(&self) -> VNode {
-                h!("button", h!("Click"))
+                h!("div", h!(comp Button))
             }
         }
 
-        let mut node = h!(comp Button);
-        assert_eq!(node.render_to_string(), "<button>Click</button>");
+        let mut node = h!(comp Div);
+        assert_eq!(node.render_to_string(), "<div><button>Click</button></div>");
+    }
+
+    fn patch(doc: &MemoryDocument, root: &MemoryNode, node: VNode, old_node: Option<VNode>, render_req: &RenderRequest) -> VNode {
+        node.patch(doc, root, None, old_node, render_req.sender())
     }
 
+    /// Holds the notifier of a component for the test to request its render.
+    type Notifier = Rc<RefCell<Option<Box<Fn()>>>>;
+
     #[test]
-    fn should_print_html_for_nested_components() {
-        struct Button;
+    fn should_patch_new_nodes_onto_memory_document() {
+        let doc = MemoryDocument::new();
+        let root = doc.create_element("main");
+        let render_req = RenderRequest::new(|| {});
+        let node = h!("div", { "class" => "container", "id" => "app" }, h!([
+            h!("span", h!("Hello")),
+            h!("World"),
+        ]));
+        patch(&doc, &root, node, None, &render_req);
+        assert_eq!(doc.inner_html(&root), r#"<div class="container" id="app"><span>Hello</span>World</div>"#);
+    }
 
-        impl Lifecycle for Button {}
-        impl Render for Button {
+    #[test]
+    fn should_patch_changed_text_and_attributes() {
+        let doc = MemoryDocument::new();
+        let root = doc.create_element("main");
+        let render_req = RenderRequest::new(|| {});
+        let old_node = patch(&doc, &root, h!("div", { "id" => "old", "title" => "stale" }, h!("Hello")), None, &render_req);
+        let div = doc.children(&root)[0];
+        let text = doc.children(&div)[0];
+        patch(&doc, &root, h!("div", { "id" => "new" }, h!("World")), Some(old_node), &render_req);
+        assert_eq!(doc.inner_html(&root), r#"<div id="new">World</div>"#);
+        // Same dom nodes are reused
+        assert_eq!(doc.children(&root), vec![div]);
+        assert_eq!(doc.children(&div), vec![text]);
+    }
+
+    #[test]
+    fn should_replace_element_with_different_tag() {
+        let doc = MemoryDocument::new();
+        let root = doc.create_element("main");
+        let render_req = RenderRequest::new(|| {});
+        let old_node = patch(&doc, &root, h!("div", h!("Hello")), None, &render_req);
+        patch(&doc, &root, h!("p", h!("Hello")), Some(old_node), &render_req);
+        assert_eq!(doc.inner_html(&root), "<p>Hello</p>");
+    }
+
+    #[test]
+    fn should_reorder_keyed_vlist() {
+        let doc = MemoryDocument::new();
+        let root = doc.create_element("ul");
+        let render_req = RenderRequest::new(|| {});
+        let old_node = patch(&doc, &root, h!({
+            "a" => h!("li", h!("a")),
+            "b" => h!("li", h!("b")),
+            "c" => h!("li", h!("c")),
+        }), None, &render_req);
+        let old_children = doc.children(&root);
+        patch(&doc, &root, h!({
+            "c" => h!("li", h!("c")),
+            "a" => h!("li", h!("a")),
+            "d" => h!("li", h!("d")),
+        }), Some(old_node), &render_req);
+        assert_eq!(doc.inner_html(&root), "<li>c</li><li>a</li><li>d</li>");
+        let new_children = doc.children(&root);
+        assert_eq!(new_children[0], old_children[2]);
+        assert_eq!(new_children[1], old_children[0]);
+        assert_eq!(doc.parent(&old_children[1]), None);
+    }
+
+    #[test]
+    fn should_insert_vlist_before_next_sibling() {
+        let doc = MemoryDocument::new();
+        let root = doc.create_element("div");
+        let render_req = RenderRequest::new(|| {});
+        let old_node = patch(&doc, &root, h!([
+            h!({ "a" => h!("a"), "b" => h!("b") }),
+            h!("!"),
+        ]), None, &render_req);
+        patch(&doc, &root, h!([
+            h!({ "b" => h!("b"), "c" => h!("c"), "a" => h!("a") }),
+            h!("!"),
+        ]), Some(old_node), &render_req);
+        assert_eq!(doc.text_content(&root), "bca!");
+    }
+
+    #[test]
+    fn should_rerender_component_with_changed_state() {
+        struct Counter {
+            count: Rc<Cell<u32>>
+        }
+
+        struct CounterProps {
+            count: Rc<Cell<u32>>,
+            notifier: Notifier
+        }
+
+        impl Component for Counter {
+            type Props = CounterProps;
+
+            fn create(props: Self::Props, notifier: Box<Fn()>) -> Self {
+                *props.notifier.borrow_mut() = Some(notifier);
+                Counter {
+                    count: props.count
+                }
+            }
+            fn update(&self, _: Self::Props) {}
+            fn eq_props(&self, _: &Self::Props) -> bool {
+                true
+            }
+        }
+
+        impl Lifecycle for Counter {}
+        impl Render for Counter {
             fn render(&self) -> VNode {
-                unimplemented!();
+                h!("span", h!(self.count.get().to_string()))
             }
         }
 
-        impl ComponentOf for Button {
-            type Comp = ButtonComponent;
+        let doc = MemoryDocument::new();
+        let root = doc.create_element("div");
+        let render_req = RenderRequest::new(|| {});
+        let count = Rc::new(Cell::new(0));
+        let notifier = Rc::new(RefCell::new(None));
+        let mut node = h!("p", h!(comp Counter, { count => count.clone(), notifier => notifier.clone() }));
+        node = patch(&doc, &root, node, None, &render_req);
+        assert_eq!(doc.inner_html(&root), "<p><span>0</span></p>");
+
+        count.set(1);
+        (notifier.borrow().as_ref().unwrap())();
+        assert!(render_req.receive());
+        node.dom_render(&doc, &root, None, render_req.sender());
+        assert_eq!(doc.inner_html(&root), "<p><span>1</span></p>");
+    }
+
+    #[test]
+    fn should_create_component_with_a_few_of_the_props() {
+        struct Label {
+            text: String,
+            color: String
         }
 
-        struct ButtonComponent {
-            inner: Rc<RefCell<Button>>
+        #[derive(Default)]
+        struct LabelProps {
+            text: Option<String>,
+            color: Option<String>
         }
 
-        impl Component for ButtonComponent {
-            type Props = ();
+        impl Component for Label {
+            type Props = LabelProps;
 
-            fn create(_: (), _: Box<Fn()>) -> Self {
-                ButtonComponent {
-                    inner: Rc::new(RefCell::new(Button))
+            fn create(props: Self::Props, _: Box<Fn()>) -> Self {
+                Label {
+                    text: props.text.unwrap_or_default(),
+                    color: props.color.unwrap_or_else(|| "black".to_string())
                 }
             }
-            fn update(&mut self, props: Self::Props) {}
-            fn props(&self) -> &Self::Props {
-                &()
+            fn update(&self, _: Self::Props) {}
+            fn eq_props(&self, _: &Self::Props) -> bool {
+                true
             }
         }
 
-        impl Lifecycle for ButtonComponent {}
-        impl Render for ButtonComponent {
+        impl Lifecycle for Label {}
+        impl Render for Label {
             fn render(&self) -> VNode {
-                h!("button", h!("Click"))
+                h!("span", { "style" => format!("color: {};", self.color) }, h!(self.text.clone()))
             }
         }
 
-        struct Div;
+        let doc = MemoryDocument::new();
+        let root = doc.create_element("div");
+        let render_req = RenderRequest::new(|| {});
+        patch(&doc, &root, h!([
+            h!(comp Label, { text => "Hello".to_string(), .. }),
+            h!(comp Label, { .. }),
+            h!(comp Label, { text => "World".to_string(), color => "red".to_string() }),
+        ]), None, &render_req);
+        assert_eq!(
+            doc.inner_html(&root),
+            "<span style=\"color: black;\">Hello</span><span style=\"color: black;\"></span><span style=\"color: red;\">World</span>"
+        );
+    }
 
-        impl Lifecycle for Div {}
-        impl Render for Div {
-            fn render(&self) -> VNode {
-                unimplemented!();
+    #[test]
+    fn should_invalidate_computed_values_which_read_the_field() {
+        let cache = ComputedCache::new();
+        let computations = Cell::new(0);
+        let compute = || {
+            computations.set(computations.get() + 1);
+            computations.get()
+        };
+        assert_eq!(cache.get_or_compute("total", &["items"], compute), 1);
+        assert_eq!(cache.get_or_compute("label", &["total"], compute), 2);
+        assert_eq!(cache.get_or_compute("total", &["items"], compute), 1);
+
+        cache.invalidate("title");
+        assert!(cache.is_cached("total"));
+        assert!(cache.is_cached("label"));
+
+        cache.invalidate("items");
+        assert!(!cache.is_cached("total"));
+        assert!(!cache.is_cached("label"));
+        assert_eq!(cache.get_or_compute("total", &["items"], compute), 3);
+    }
+
+    struct Tracked {
+        hooks: Rc<RefCell<Vec<&'static str>>>,
+        skip_render: Rc<Cell<bool>>,
+        count: Rc<Cell<u32>>
+    }
+
+    struct TrackedProps {
+        hooks: Rc<RefCell<Vec<&'static str>>>,
+        skip_render: Rc<Cell<bool>>,
+        count: Rc<Cell<u32>>,
+        notifier: Notifier
+    }
+
+    impl Tracked {
+        fn track(&self, hook: &'static str) {
+            self.hooks.borrow_mut().push(hook);
+        }
+    }
+
+    impl Component for Tracked {
+        type Props = TrackedProps;
+
+        fn create(props: Self::Props, notifier: Box<Fn()>) -> Self {
+            *props.notifier.borrow_mut() = Some(notifier);
+            Tracked {
+                hooks: props.hooks,
+                skip_render: props.skip_render,
+                count: props.count
             }
         }
-        impl ComponentOf for Div {
-            type Comp = DivComponent;
+        fn update(&self, _: Self::Props) {}
+        fn eq_props(&self, _: &Self::Props) -> bool {
+            true
+        }
+    }
+
+    impl Lifecycle for Tracked {
+        fn created(&self) {
+            self.track("created");
+        }
+        fn before_mount(&self) {
+            self.track("before_mount");
+        }
+        fn mounted(&self) {
+            self.track("mounted");
+        }
+        fn should_render(&self) -> bool {
+            self.track("should_render");
+            !self.skip_render.get()
+        }
+        fn before_update(&self) {
+            self.track("before_update");
+        }
+        fn updated(&self) {
+            self.track("updated");
+        }
+        fn before_destroy(&self) {
+            self.track("before_destroy");
+        }
+        fn destroyed(&self) {
+            self.track("destroyed");
         }
+    }
 
-        struct DivComponent {
-            inner: Rc<RefCell<Div>>
+    impl Render for Tracked {
+        fn render(&self) -> VNode {
+            self.track("render");
+            h!("span", h!(self.count.get().to_string()))
         }
+    }
+
+    #[test]
+    fn should_call_lifecycle_hooks_in_order() {
+        let doc = MemoryDocument::new();
+        let root = doc.create_element("div");
+        let render_req = RenderRequest::new(|| {});
+        let hooks = Rc::new(RefCell::new(vec![]));
+        let count = Rc::new(Cell::new(0));
+        let notifier = Rc::new(RefCell::new(None));
+        let props = || TrackedProps {
+            hooks: hooks.clone(),
+            skip_render: Rc::new(Cell::new(false)),
+            count: count.clone(),
+            notifier: notifier.clone()
+        };
+        let mut node = patch(&doc, &root, VNode::Component(VComponent::new::<Tracked>(props())), None, &render_req);
+        assert_eq!(*hooks.borrow(), vec!["created", "before_mount", "render", "mounted"]);
+
+        hooks.borrow_mut().clear();
+        count.set(1);
+        (notifier.borrow().as_ref().unwrap())();
+        node.dom_render(&doc, &root, None, render_req.sender());
+        assert_eq!(*hooks.borrow(), vec!["should_render", "before_update", "render", "updated"]);
+        assert_eq!(doc.inner_html(&root), "<span>1</span>");
+
+        hooks.borrow_mut().clear();
+        node.dom_render(&doc, &root, None, render_req.sender());
+        assert!(hooks.borrow().is_empty());
+
+        patch(&doc, &root, h!("p", _), Some(node), &render_req);
+        assert_eq!(*hooks.borrow(), vec!["before_destroy", "destroyed"]);
+        assert_eq!(doc.inner_html(&root), "<p></p>");
+    }
+
+    #[test]
+    fn should_skip_render_when_component_should_not_render() {
+        let doc = MemoryDocument::new();
+        let root = doc.create_element("div");
+        let render_req = RenderRequest::new(|| {});
+        let hooks = Rc::new(RefCell::new(vec![]));
+        let skip_render = Rc::new(Cell::new(true));
+        let count = Rc::new(Cell::new(0));
+        let notifier = Rc::new(RefCell::new(None));
+        let mut node = patch(&doc, &root, VNode::Component(VComponent::new::<Tracked>(TrackedProps {
+            hooks: hooks.clone(),
+            skip_render: skip_render.clone(),
+            count: count.clone(),
+            notifier: notifier.clone()
+        })), None, &render_req);
+        assert_eq!(doc.inner_html(&root), "<span>0</span>");
+
+        hooks.borrow_mut().clear();
+        count.set(1);
+        (notifier.borrow().as_ref().unwrap())();
+        node.dom_render(&doc, &root, None, render_req.sender());
+        assert_eq!(*hooks.borrow(), vec!["should_render"]);
+        assert_eq!(doc.inner_html(&root), "<span>0</span>");
+
+        // The skipped change is not rendered later on without another change
+        hooks.borrow_mut().clear();
+        skip_render.set(false);
+        node.dom_render(&doc, &root, None, render_req.sender());
+        assert!(hooks.borrow().is_empty());
+        (notifier.borrow().as_ref().unwrap())();
+        node.dom_render(&doc, &root, None, render_req.sender());
+        assert_eq!(doc.inner_html(&root), "<span>1</span>");
+    }
+
+    #[test]
+    fn should_only_move_nodes_when_rekeying_vlist() {
+        let doc = MemoryDocument::new();
+        let root = doc.create_element("ul");
+        let render_req = RenderRequest::new(|| {});
+        let old_node = patch(&doc, &root, h!({
+            "a" => h!("li", h!("a")),
+            "b" => h!("li", h!("b")),
+            "c" => h!("li", h!("c")),
+        }), None, &render_req);
+        let old_children = doc.children(&root);
+        doc.clear_operations();
+        patch(&doc, &root, h!({
+            "a" => h!("li", h!("a")),
+            "c" => h!("li", h!("c")),
+            "b" => h!("li", h!("b")),
+        }), Some(old_node), &render_req);
+        assert_eq!(doc.inner_html(&root), "<li>a</li><li>c</li><li>b</li>");
+        assert_eq!(doc.moved(), vec![old_children[1], old_children[2]]);
+        assert!(doc.created().is_empty());
+        assert!(doc.removed().is_empty());
+    }
+
+    #[test]
+    fn should_log_creations_and_removals_of_vlist_items() {
+        let doc = MemoryDocument::new();
+        let root = doc.create_element("ul");
+        let render_req = RenderRequest::new(|| {});
+        let old_node = patch(&doc, &root, h!({
+            "a" => h!("li", _),
+            "b" => h!("li", _),
+        }), None, &render_req);
+        let old_children = doc.children(&root);
+        doc.clear_operations();
+        patch(&doc, &root, h!({
+            "a" => h!("li", _),
+            "c" => h!("li", { "id" => "c" }),
+        }), Some(old_node), &render_req);
+        let new_children = doc.children(&root);
+        assert_eq!(doc.operations(), vec![
+            Operation::Created(new_children[1]),
+            Operation::AttributeSet(new_children[1], "id".to_string(), "c".to_string()),
+            Operation::Inserted(new_children[1]),
+            Operation::Removed(old_children[1]),
+        ]);
+    }
+
+    #[test]
+    fn should_escape_html_in_text() {
+        let node = h!("p", h!("<script>alert('Tom & Jerry')</script>"));
+        assert_eq!(node.to_string(), "<p>&lt;script&gt;alert(&#39;Tom &amp; Jerry&#39;)&lt;/script&gt;</p>");
+    }
+
+    #[test]
+    fn should_escape_html_in_attributes() {
+        let node = h!("a", { "class" => "\"><script>", "title" => "Say \"Hi\" & <wave>" });
+        assert_eq!(node.to_string(), r#"<a class="&quot;&gt;&lt;script&gt;" title="Say &quot;Hi&quot; &amp; &lt;wave&gt;"></a>"#);
+    }
+
+    #[test]
+    fn should_not_escape_the_text_of_script_and_style() {
+        let node = h!("div", vec![
+            h!("script", h!("if (a < b && c) {}")),
+            h!("style", h!("a > b { content: '&'; }")),
+            h!("p", h!("a < b")),
+        ]);
+        let html = "<div><script>if (a < b && c) {}</script><style>a > b { content: '&'; }</style><p>a &lt; b</p></div>";
+        assert_eq!(node.to_string(), html);
 
-        impl Component for DivComponent {
+        let mut written = vec![];
+        node.render_to_writer(&mut written).unwrap();
+        assert_eq!(String::from_utf8(written).unwrap(), html);
+    }
+
+    #[test]
+    fn should_reject_the_end_tag_within_script_and_style() {
+        assert_eq!(
+            VElement::try_new("script".into(), None, None, vec![h!("alert('</SCRIPT>')")]),
+            Err(ValidationError::EndTagWithinRawText("script".to_string()))
+        );
+        assert_eq!(
+            VElement::try_new("style".into(), None, None, vec![h!("a { content: '</style'; }")]),
+            Err(ValidationError::EndTagWithinRawText("style".to_string()))
+        );
+        assert!(VElement::try_new("script".into(), None, None, vec![h!("'</div>'")]).is_ok());
+    }
+
+    #[test]
+    fn should_escape_html_in_rendered_components() {
+        struct Comment;
+
+        impl Component for Comment {
             type Props = ();
 
-            fn create(_:(), _: Box<Fn()>) -> Self {
-                DivComponent {
-                    inner: Rc::new(RefCell::new(Div))
-                }
+            fn create(_: (), _: Box<Fn()>) -> Self {
+                Comment
             }
-            fn update(&mut self, props: Self::Props) {}
-            fn props(&self) -> &Self::Props {
-                &()
+            fn update(&self, _: Self::Props) {}
+            fn eq_props(&self, _: &Self::Props) -> bool {
+                true
             }
         }
 
-        impl Lifecycle for DivComponent {}
-        impl Render for DivComponent {
+        impl Lifecycle for Comment {}
+        impl Render for Comment {
             fn render(&self) -> VNode {
-                h!("div", h!(comp Button))
+                h!("div", { "data-author" => "O'Brien" }, h!("1 < 2"))
             }
         }
 
-        let mut node = h!(comp Div);
-        assert_eq!(node.render_to_string(), "<div><button>Click</button></div>");
+        let mut node = h!(comp Comment);
+        assert_eq!(node.render_to_string(), r#"<div data-author="O&#39;Brien">1 &lt; 2</div>"#);
+    }
+
+    #[test]
+    fn should_create_raw_vnode() {
+        let node = h!(raw "<b>Bold</b>");
+        assert_eq!(VNode::Raw(VRaw::new("<b>Bold</b>".into())), node);
+    }
+
+    #[test]
+    fn should_print_html_for_raw_without_escaping() {
+        let node = h!("article", h!([
+            h!(raw "<h1>Title</h1><p>Body</p>"),
+            h!("<p>"),
+        ]));
+        assert_eq!(node.to_string(), "<article><h1>Title</h1><p>Body</p>&lt;p&gt;</article>");
+    }
+
+    #[test]
+    fn should_replace_raw_html_only_when_changed() {
+        let doc = MemoryDocument::new();
+        let root = doc.create_element("div");
+        let render_req = RenderRequest::new(|| {});
+        let old_node = patch(&doc, &root, h!([h!(raw "<b>Hi</b>"), h!("!")]), None, &render_req);
+        doc.clear_operations();
+        let old_node = patch(&doc, &root, h!([h!(raw "<b>Hi</b>"), h!("!")]), Some(old_node), &render_req);
+        assert!(doc.operations().is_empty());
+
+        let old_children = doc.children(&root);
+        patch(&doc, &root, h!([h!(raw "<i>Bye</i>"), h!("!")]), Some(old_node), &render_req);
+        assert_eq!(doc.inner_html(&root), "<i>Bye</i>!");
+        assert_eq!(doc.removed(), vec![old_children[0]]);
+    }
+
+    #[test]
+    fn should_reorder_and_remove_raw_html() {
+        let doc = MemoryDocument::new();
+        let root = doc.create_element("div");
+        let render_req = RenderRequest::new(|| {});
+        let old_node = patch(&doc, &root, h!({ "a" => h!(raw "<hr>"), "b" => h!("b") }), None, &render_req);
+        let old_node = patch(&doc, &root, h!({ "b" => h!("b"), "a" => h!(raw "<hr>") }), Some(old_node), &render_req);
+        assert_eq!(doc.inner_html(&root), "b<hr>");
+        patch(&doc, &root, h!({ "b" => h!("b") }), Some(old_node), &render_req);
+        assert_eq!(doc.inner_html(&root), "b");
+    }
+
+    #[test]
+    fn should_create_velement_with_multiple_children() {
+        let node = h!("div", { "id" => "app" }, h!("span", _), h!("Hello"), h!("br", _));
+        assert_eq!(
+            VNode::Element(VElement::new(
+                "div".into(),
+                None,
+                Some(vec![("id", "app")].into()),
+                vec![
+                    VNode::Element(VElement::new("span".into(), None, None, vec![])),
+                    VNode::Text(VText::new("Hello".into())),
+                    VNode::Element(VElement::new("br".into(), None, None, vec![])),
+                ])
+            ),
+            node
+        );
+    }
+
+    #[test]
+    fn should_print_html_for_multiple_children() {
+        let node = h!("p", { "class" => "text" }, h!("Hello"), h!("b", h!("World")), h!("!"));
+        assert_eq!(node.to_string(), r#"<p class="text">Hello<b>World</b>!</p>"#);
+    }
+
+    #[test]
+    fn should_patch_multiple_children_positionally() {
+        let doc = MemoryDocument::new();
+        let root = doc.create_element("div");
+        let render_req = RenderRequest::new(|| {});
+        let old_node = patch(&doc, &root, h!("p", { "class" => "text" }, h!("a"), h!("b", _), h!("c")), None, &render_req);
+        let p = doc.children(&root)[0];
+        let old_children = doc.children(&p);
+        doc.clear_operations();
+
+        let old_node = patch(&doc, &root, h!("p", { "class" => "text" }, h!("x"), h!("i", _), h!("c"), h!("d")), Some(old_node), &render_req);
+        assert_eq!(doc.inner_html(&root), r#"<p class="text">x<i></i>cd</p>"#);
+        let new_children = doc.children(&p);
+        assert_eq!(new_children[0], old_children[0]);
+        assert_eq!(new_children[2], old_children[2]);
+        assert_eq!(doc.removed(), vec![old_children[1]]);
+        assert!(doc.moved().is_empty());
+
+        patch(&doc, &root, h!("p", { "class" => "text" }, h!("x")), Some(old_node), &render_req);
+        assert_eq!(doc.inner_html(&root), r#"<p class="text">x</p>"#);
+    }
+
+    #[test]
+    fn should_print_html_for_boolean_attributes() {
+        let node = h!("button", { "disabled" => false, "hidden" => true, "title" => None::<&str> }, h!("Click"));
+        assert_eq!(node.to_string(), "<button hidden>Click</button>");
+    }
+
+    #[test]
+    fn should_print_html_for_properties_as_attributes() {
+        let node = h!("div", { "class" => "form" },
+            h!("input", { "type" => "checkbox", "checked" => true }),
+            h!("input", { "value" => "Hello \"World\"" }),
+            h!("option", { "selected" => false }, h!("One")));
+        assert_eq!(
+            node.to_string(),
+            r#"<div class="form"><input type="checkbox" checked><input value="Hello &quot;World&quot;"><option>One</option></div>"#
+        );
+    }
+
+    #[test]
+    fn should_patch_boolean_attributes() {
+        let doc = MemoryDocument::new();
+        let root = doc.create_element("div");
+        let render_req = RenderRequest::new(|| {});
+        let old_node = patch(&doc, &root, h!("button", { "disabled" => true, "hidden" => false }), None, &render_req);
+        assert_eq!(doc.inner_html(&root), r#"<button disabled=""></button>"#);
+        patch(&doc, &root, h!("button", { "disabled" => false, "hidden" => true }), Some(old_node), &render_req);
+        assert_eq!(doc.inner_html(&root), r#"<button hidden=""></button>"#);
+    }
+
+    #[test]
+    fn should_always_patch_live_properties() {
+        let doc = MemoryDocument::new();
+        let root = doc.create_element("div");
+        let render_req = RenderRequest::new(|| {});
+        let old_node = patch(&doc, &root, h!("input", { "value" => "Hello", "checked" => true }), None, &render_req);
+        let input = doc.children(&root)[0];
+        assert_eq!(doc.inner_html(&root), "<input>");
+        assert_eq!(doc.property(&input, "value"), Some(AttrValue::Str("Hello".into())));
+        assert_eq!(doc.property(&input, "checked"), Some(AttrValue::Bool(true)));
+
+        // The user edits the input
+        doc.set_property(&input, "value", &AttrValue::Str("Hello Wor".into()));
+        let old_node = patch(&doc, &root, h!("input", { "value" => "Hello", "checked" => true }), Some(old_node), &render_req);
+        assert_eq!(doc.property(&input, "value"), Some(AttrValue::Str("Hello".into())));
+
+        patch(&doc, &root, h!("input", { "value" => "Hello" }), Some(old_node), &render_req);
+        assert_eq!(doc.property(&input, "checked"), None);
+    }
+
+    #[test]
+    fn should_print_html_for_conditional_classes_and_style() {
+        let is_active = true;
+        let is_disabled = false;
+        let node = h!("button", {
+            "class" => classes!("btn", "active" => is_active, "disabled" => is_disabled),
+            "style" => style!("color" => "red", "margin-top" => "2px")
+        }, h!("Click"));
+        assert_eq!(node.to_string(), r#"<button class="btn active" style="color: red; margin-top: 2px;">Click</button>"#);
+    }
+
+    #[test]
+    fn should_parse_class_and_style_strings() {
+        let node = h!("div", { "class" => " a  b a ", "style" => "color:red ;; width: 1px" });
+        assert_eq!(node, h!("div", { "class" => classes!("a", "b"), "style" => style!("color" => "red", "width" => "1px") }));
+        assert_eq!(node.to_string(), r#"<div class="a b" style="color: red; width: 1px;"></div>"#);
+    }
+
+    #[test]
+    fn should_not_split_the_style_within_parentheses_and_quotes() {
+        let node = h!("div", { "style" => "color: red; background: url(data:image/png;base64,AA==); content: 'a;b'" });
+        assert_eq!(node, h!("div", { "style" => style!(
+            "color" => "red",
+            "background" => "url(data:image/png;base64,AA==)",
+            "content" => "'a;b'"
+        ) }));
+        assert_eq!(
+            node.to_string(),
+            r#"<div style="color: red; background: url(data:image/png;base64,AA==); content: &#39;a;b&#39;;"></div>"#
+        );
+    }
+
+    #[test]
+    fn should_patch_classes_and_style_per_item() {
+        let doc = MemoryDocument::new();
+        let root = doc.create_element("div");
+        let render_req = RenderRequest::new(|| {});
+        let old_node = patch(&doc, &root, h!("p", {
+            "class" => classes!("a", "b"),
+            "style" => style!("color" => "red", "width" => "1px")
+        }), None, &render_req);
+        let p = doc.children(&root)[0];
+        assert_eq!(doc.inner_html(&root), r#"<p class="a b" style="color: red; width: 1px;"></p>"#);
+
+        doc.clear_operations();
+        let old_node = patch(&doc, &root, h!("p", {
+            "class" => classes!("b", "c"),
+            "style" => style!("color" => "blue", "width" => "1px")
+        }), Some(old_node), &render_req);
+        assert_eq!(doc.operations(), vec![
+            Operation::ClassRemoved(p, "a".to_string()),
+            Operation::ClassAdded(p, "c".to_string()),
+            Operation::StyleSet(p, "color".to_string(), "blue".to_string()),
+        ]);
+        assert_eq!(doc.inner_html(&root), r#"<p class="b c" style="color: blue; width: 1px;"></p>"#);
+
+        doc.clear_operations();
+        patch(&doc, &root, h!("p", { "class" => "c" }), Some(old_node), &render_req);
+        assert_eq!(doc.operations(), vec![
+            Operation::ClassRemoved(p, "b".to_string()),
+            Operation::AttributeRemoved(p, "style".to_string()),
+        ]);
+        assert_eq!(doc.inner_html(&root), r#"<p class="c"></p>"#);
+    }
+
+    #[test]
+    fn should_print_html_with_xmlns_for_svg() {
+        let node = h!("div", h!("svg", { "viewBox" => "0 0 10 10" },
+            h!("circle", { "r" => "1" }),
+            h!("foreignObject", h!("p", h!("Hello")))));
+        assert_eq!(
+            node.to_string(),
+            r#"<div><svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10"><circle r="1"></circle><foreignObject><p>Hello</p></foreignObject></svg></div>"#
+        );
+    }
+
+    #[test]
+    fn should_only_treat_html_elements_as_void() {
+        let node = h!("math", (h!("img", _), h!("source", { "xmlns" => Namespace::Svg.uri() }, h!("a"))));
+        assert_eq!(
+            node.to_string(),
+            r#"<math xmlns="http://www.w3.org/1998/Math/MathML"><img></img><source xmlns="http://www.w3.org/2000/svg">a</source></math>"#
+        );
+    }
+
+    #[test]
+    fn should_patch_namespaced_elements_along_with_components() {
+        struct Icon;
+
+        impl Component for Icon {
+            type Props = ();
+
+            fn create(_: (), _: Box<Fn()>) -> Self {
+                Icon
+            }
+            fn update(&self, _: Self::Props) {}
+            fn eq_props(&self, _: &Self::Props) -> bool {
+                true
+            }
+        }
+
+        impl Lifecycle for Icon {}
+        impl Render for Icon {
+            fn render(&self) -> VNode {
+                h!("path", { "d" => "M0 0" })
+            }
+        }
+
+        let doc = MemoryDocument::new();
+        let root = doc.create_element("div");
+        let render_req = RenderRequest::new(|| {});
+        let old_node = patch(&doc, &root, h!("svg", (h!("g", h!(comp Icon)), h!("foreignObject", h!("p", _)))), None, &render_req);
+        let svg = doc.children(&root)[0];
+        let g = doc.children(&svg)[0];
+        let path = doc.children(&g)[0];
+        let p = doc.children(&doc.children(&svg)[1])[0];
+        assert_eq!(doc.namespace(&svg), Some(Namespace::Svg.uri().to_string()));
+        assert_eq!(doc.namespace(&g), Some(Namespace::Svg.uri().to_string()));
+        assert_eq!(doc.namespace(&path), Some(Namespace::Svg.uri().to_string()));
+        assert_eq!(doc.namespace(&p), None);
+
+        // Recreated once the namespace of the same tag changes
+        patch(&doc, &root, h!("svg", (h!("g", h!(comp Icon)), h!("foreignObject", h!("p", { "xmlns" => Namespace::MathMl.uri() })))), Some(old_node), &render_req);
+        let new_p = doc.children(&doc.children(&svg)[1])[0];
+        assert_eq!(doc.removed(), vec![p]);
+        assert_eq!(doc.namespace(&new_p), Some(Namespace::MathMl.uri().to_string()));
+    }
+
+    #[test]
+    fn should_validate_tag_and_attribute_names() {
+        assert_eq!(
+            VElement::try_new("my div".into(), None, None, vec![]),
+            Err(ValidationError::InvalidTagName("my div".to_string()))
+        );
+        assert_eq!(
+            VElement::try_new("1p".into(), None, None, vec![]),
+            Err(ValidationError::InvalidTagName("1p".to_string()))
+        );
+        assert_eq!(
+            VElement::try_new("a".into(), None, Some(vec![("on click", "")].into()), vec![]),
+            Err(ValidationError::InvalidAttributeName("on click".to_string()))
+        );
+        assert_eq!(
+            VElement::try_new("br".into(), None, None, vec![h!("a")]),
+            Err(ValidationError::VoidElementWithChildren("br".to_string()))
+        );
+        assert!(VElement::try_new("my-element".into(), None, Some(vec![("data-id", "1")].into()), vec![]).is_ok());
+    }
+
+    #[test]
+    fn should_pass_the_unknown_tags_to_the_warning_handler_once() {
+        let warnings = Rc::new(RefCell::new(vec![]));
+        let handled = warnings.clone();
+        set_warning_handler(move |message| handled.borrow_mut().push(message.to_string()));
+        set_strict_mode(true);
+
+        let mut node = h!("div", vec![h!("dvi", _), h!("DVI", _), h!("span", _)]);
+        node.render_to_string();
+        node.render_to_string();
+        assert_eq!(*warnings.borrow(), vec!["`dvi` is not a known html tag".to_string()]);
+    }
+
+    #[test]
+    #[should_panic(expected = "`a>` is not a valid tag name")]
+    fn should_panic_on_invalid_tag_name() {
+        h!("a>", _);
+    }
+
+    #[test]
+    fn should_know_html_tags() {
+        assert!(is_known_html_tag("div"));
+        assert!(is_known_html_tag("TEXTAREA"));
+        assert!(!is_known_html_tag("dvi"));
+        assert!(!is_known_html_tag("my-element"));
+    }
+
+    #[test]
+    fn should_rebind_component_events_on_every_patch() {
+        struct ToggleProps {
+            handle: Rc<RefCell<Option<Callback<bool>>>>,
+        }
+
+        struct Toggle {
+            on_toggle: Callback<bool>,
+        }
+
+        impl Toggle {
+            fn on_toggle(&self) -> Callback<bool> {
+                self.on_toggle.clone()
+            }
+        }
+
+        impl Component for Toggle {
+            type Props = ToggleProps;
+
+            fn create(props: Self::Props, _: Box<Fn()>) -> Self {
+                let on_toggle = Callback::new();
+                *props.handle.borrow_mut() = Some(on_toggle.clone());
+                Toggle { on_toggle }
+            }
+            fn update(&self, _: Self::Props) {}
+            fn eq_props(&self, _: &Self::Props) -> bool {
+                true
+            }
+        }
+
+        impl Lifecycle for Toggle {}
+        impl Render for Toggle {
+            fn render(&self) -> VNode {
+                h!("button", h!("Toggle"))
+            }
+        }
+
+        let doc = MemoryDocument::new();
+        let root = doc.create_element("div");
+        let render_req = RenderRequest::new(|| {});
+        let handle = Rc::new(RefCell::new(None));
+        let emit = |value| handle.borrow().as_ref().map(|it: &Callback<bool>| it.emit(value));
+
+        let first = Rc::new(RefCell::new(vec![]));
+        let first_writer = first.clone();
+        let old_node = patch(&doc, &root, h!(comp Toggle, { handle => handle.clone() }, [
+            on_toggle => move |on| first_writer.borrow_mut().push(on)
+        ]), None, &render_req);
+        emit(true);
+        assert_eq!(*first.borrow(), vec![true]);
+
+        // The same instance is now bound to the listener of the newer render
+        let second = Rc::new(RefCell::new(vec![]));
+        let second_writer = second.clone();
+        let old_node = patch(&doc, &root, h!(comp Toggle, { handle => handle.clone() }, [
+            on_toggle => move |on| second_writer.borrow_mut().push(on)
+        ]), Some(old_node), &render_req);
+        emit(false);
+        assert_eq!(*first.borrow(), vec![true]);
+        assert_eq!(*second.borrow(), vec![false]);
+
+        patch(&doc, &root, h!(comp Toggle, { handle => handle.clone() }), Some(old_node), &render_req);
+        emit(true);
+        assert_eq!(*second.borrow(), vec![false]);
+    }
+
+    struct Theme;
+
+    impl Component for Theme {
+        type Props = Rc<RefCell<Option<Context<&'static str>>>>;
+
+        fn create(handle: Self::Props, _: Box<Fn()>) -> Self {
+            *handle.borrow_mut() = Some(context::provide("light"));
+            Theme
+        }
+        fn update(&self, _: Self::Props) {}
+        fn eq_props(&self, _: &Self::Props) -> bool {
+            true
+        }
+    }
+
+    impl Lifecycle for Theme {}
+    impl Render for Theme {
+        fn render(&self) -> VNode {
+            h!("main", h!("div", h!(comp Themed)))
+        }
+    }
+
+    struct Themed;
+
+    impl Component for Themed {
+        type Props = ();
+
+        fn create(_: (), _: Box<Fn()>) -> Self {
+            Themed
+        }
+        fn update(&self, _: Self::Props) {}
+        fn eq_props(&self, _: &Self::Props) -> bool {
+            true
+        }
+    }
+
+    impl Lifecycle for Themed {}
+    impl Render for Themed {
+        fn render(&self) -> VNode {
+            let theme = context::consume::<&'static str>().expect("The theme is provided by an ancestor");
+            let theme = *theme.get();
+            h!("p", { "class" => theme }, h!(theme))
+        }
+    }
+
+    #[test]
+    fn should_rerender_context_consumers_on_change() {
+        let doc = MemoryDocument::new();
+        let root = doc.create_element("div");
+        let render_req = RenderRequest::new(|| {});
+        let handle = Rc::new(RefCell::new(None));
+        let mut node = patch(&doc, &root, VNode::Component(VComponent::new::<Theme>(handle.clone())), None, &render_req);
+        assert_eq!(doc.inner_html(&root), r#"<main><div><p class="light">light</p></div></main>"#);
+        assert!(!render_req.receive());
+
+        handle.borrow().as_ref().unwrap().set("dark");
+        assert!(render_req.receive());
+        node.dom_render(&doc, &root, None, render_req.sender());
+        assert_eq!(doc.inner_html(&root), r#"<main><div><p class="dark">dark</p></div></main>"#);
+    }
+
+    #[test]
+    fn should_server_render_context_consumers() {
+        let mut node = VNode::Component(VComponent::new::<Theme>(Rc::new(RefCell::new(None))));
+        assert_eq!(node.render_to_string(), r#"<main><div><p class="light">light</p></div></main>"#);
+        assert!(context::consume::<&'static str>().is_none());
+    }
+
+    #[test]
+    fn should_stream_the_same_html_as_render_to_string() {
+        let app = || h!("div", { "class" => "app" }, h!([
+            h!("br", _),
+            h!("svg", h!("circle", _)),
+            h!(raw "<b>Raw</b>"),
+            VNode::Component(VComponent::new::<Theme>(Rc::new(RefCell::new(None)))),
+        ]));
+        let mut html = vec![];
+        app().render_to_writer(&mut html).unwrap();
+        assert_eq!(String::from_utf8(html).unwrap(), app().render_to_string());
+    }
+
+    /// Records each chunk written, along with the renders of the `Deep` components in between.
+    #[derive(Clone)]
+    struct Chunks(Rc<RefCell<Vec<String>>>);
+
+    impl Write for Chunks {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().push(String::from_utf8_lossy(buf).into_owned());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    struct Deep(Chunks);
+
+    impl Component for Deep {
+        type Props = Chunks;
+
+        fn create(props: Self::Props, _: Box<Fn()>) -> Self {
+            Deep(props)
+        }
+        fn update(&self, _: Self::Props) {}
+        fn eq_props(&self, _: &Self::Props) -> bool {
+            true
+        }
+    }
+
+    impl Lifecycle for Deep {}
+    impl Render for Deep {
+        fn render(&self) -> VNode {
+            (self.0).0.borrow_mut().push("(render)".to_string());
+            h!("p", h!("Deep"))
+        }
+    }
+
+    #[test]
+    fn should_render_components_only_when_reached_while_streaming() {
+        let mut chunks = Chunks(Rc::new(RefCell::new(vec![])));
+        let node = h!("main", h!([
+            h!("h1", h!("Title")),
+            VNode::Component(VComponent::new::<Deep>(chunks.clone())),
+        ]));
+        node.render_to_writer(&mut chunks).unwrap();
+        assert_eq!(chunks.0.borrow().concat(), "<main><h1>Title</h1>(render)<p>Deep</p></main>");
+    }
+
+    struct Page;
+
+    impl Component for Page {
+        type Props = ();
+
+        fn create(_: (), _: Box<Fn()>) -> Self {
+            head::title("Home");
+            head::meta("description", vec![("name", "description"), ("content", "The home page")]);
+            Page
+        }
+        fn update(&self, _: Self::Props) {}
+        fn eq_props(&self, _: &Self::Props) -> bool {
+            true
+        }
+    }
+
+    impl Lifecycle for Page {}
+    impl Render for Page {
+        fn render(&self) -> VNode {
+            h!("main", h!(comp Article))
+        }
+    }
+
+    struct Article;
+
+    impl Component for Article {
+        type Props = ();
+
+        fn create(_: (), _: Box<Fn()>) -> Self {
+            Article
+        }
+        fn update(&self, _: Self::Props) {}
+        fn eq_props(&self, _: &Self::Props) -> bool {
+            true
+        }
+    }
+
+    impl Lifecycle for Article {}
+    impl Render for Article {
+        fn render(&self) -> VNode {
+            head::title("Article & more");
+            head::link("style", vec![("rel", "stylesheet"), ("href", "/article.css")]);
+            head::script("state", r#"window.state = "</script>";"#);
+            h!("article", h!("Hello"))
+        }
+    }
+
+    #[test]
+    fn should_render_a_document_with_the_head_added_by_components() {
+        let mut document = Document::new(h!(comp Page), "app")
+            .head("viewport", ("meta", vec![("name", "viewport"), ("content", "width=device-width")]))
+            .head("title", ("title", h!("Untitled")));
+        assert_eq!(document.render_to_string(), concat!(
+            "<!DOCTYPE html><html><head>",
+            r#"<meta charset="utf-8"><meta name="viewport" content="width=device-width">"#,
+            "<title>Article &amp; more</title>",
+            r#"<meta name="description" content="The home page">"#,
+            r#"<link rel="stylesheet" href="/article.css">"#,
+            r#"<script>window.state = "<\/script>";</script>"#,
+            r#"</head><body><div id="app"><main><article>Hello</article></main></div></body></html>"#
+        ));
+    }
+
+    #[test]
+    fn should_stream_a_document_with_the_head_added_before_the_app_is_written() {
+        let warnings = Rc::new(RefCell::new(vec![]));
+        let handled = warnings.clone();
+        set_warning_handler(move |message| handled.borrow_mut().push(message.to_string()));
+
+        let document = Document::new(h!(comp Page), "app")
+            .head("title", ("title", h!("Untitled")));
+        let mut html = vec![];
+        document.render_to_writer(&mut html).unwrap();
+        assert_eq!(String::from_utf8(html).unwrap(), concat!(
+            r#"<!DOCTYPE html><html><head><meta charset="utf-8"><title>Home</title>"#,
+            r#"<meta name="description" content="The home page">"#,
+            r#"</head><body><div id="app"><main><article>Hello</article></main></div></body></html>"#
+        ));
+        // The `Article` is rendered after the `<main>` is written
+        assert_eq!(*warnings.borrow(), vec![
+            "The head entry `title` is added after the head is streamed".to_string(),
+            "The head entry `style` is added after the head is streamed".to_string(),
+            "The head entry `state` is added after the head is streamed".to_string(),
+        ]);
+    }
+
+    #[test]
+    fn should_ignore_the_head_when_not_rendering_a_document() {
+        let mut node = h!(comp Page);
+        assert_eq!(node.render_to_string(), "<main><article>Hello</article></main>");
+        let mut head = head::Head::new();
+        head::collect(&mut head, || {});
+        assert!(head.is_empty());
+    }
+
+    #[test]
+    fn should_adopt_the_server_rendered_nodes_when_hydrating() {
+        let doc = MemoryDocument::new();
+        let root = doc.create_element("div");
+        let render_req = RenderRequest::new(|| {});
+        let app = |greeting: &'static str| h!("div", { "class" => "app" }, h!([
+            h!("p", h!(greeting)),
+            h!("input", { "value" => "Hi" }),
+            h!({
+                "a" => h!("li", _),
+                "b" => h!("li", _),
+            }),
+        ]));
+        // Rendered by the server
+        patch(&doc, &root, app("Hello"), None, &render_req);
+        let adopted = doc.children(&root);
+        doc.clear_operations();
+
+        let (node, mismatches) = hydrate(app("Hello"), &doc, &root, SavedStates::new(), render_req.sender());
+        assert!(mismatches.is_empty());
+        let input = doc.children(&adopted[0])[1];
+        assert_eq!(doc.operations(), vec![Operation::PropertySet(input, "value".to_string(), AttrValue::Str("Hi".into()))]);
+        assert_eq!(doc.children(&root), adopted);
+
+        // Later patches are done on the adopted nodes
+        doc.clear_operations();
+        patch(&doc, &root, app("Hello World"), Some(node), &render_req);
+        let text = doc.children(&doc.children(&adopted[0])[0])[0];
+        assert!(doc.operations().contains(&Operation::TextSet(text, "Hello World".to_string())));
+        assert!(doc.created().is_empty());
+        assert_eq!(doc.inner_html(&root), r#"<div class="app"><p>Hello World</p><input><li></li><li></li></div>"#);
+    }
+
+    #[test]
+    fn should_split_the_merged_server_rendered_texts_when_hydrating() {
+        let doc = MemoryDocument::new();
+        let root = doc.create_element("div");
+        let render_req = RenderRequest::new(|| {});
+        // Parsed as a single text node by the browser
+        patch(&doc, &root, h!("p", h!("Hello World")), None, &render_req);
+        let p = doc.children(&root)[0];
+        let text = doc.children(&p)[0];
+
+        let (_, mismatches) = hydrate(h!("p", h!([h!("Hello "), h!(""), h!("World")])), &doc, &root, SavedStates::new(), render_req.sender());
+        assert!(mismatches.is_empty());
+        let texts = doc.children(&p);
+        assert_eq!(texts.len(), 3);
+        assert_eq!(texts[0], text);
+        assert_eq!(doc.text_content(&texts[0]), "Hello ");
+        assert_eq!(doc.text_content(&texts[1]), "");
+        assert_eq!(doc.text_content(&texts[2]), "World");
+    }
+
+    #[test]
+    fn should_adopt_the_raw_html_nodes_without_parsing_them_when_hydrating() {
+        let doc = MemoryDocument::new();
+        let root = doc.create_element("div");
+        let render_req = RenderRequest::new(|| {});
+        // As parsed by the browser, with the trailing text of the raw html merged with the next one
+        let img = doc.create_element("img");
+        let b = doc.create_element("b");
+        doc.append_child(&b, &doc.create_text("Bold"));
+        let text = doc.create_text("tail & World");
+        for node in &[&img, &b, &text] {
+            doc.append_child(&root, node);
+        }
+        doc.clear_operations();
+
+        let (node, mismatches) = hydrate(
+            h!([h!(raw r#"<img src="a>b"><b>Bold</b>tail &amp; "#), h!("World")]),
+            &doc, &root, SavedStates::new(), render_req.sender()
+        );
+        assert!(mismatches.is_empty());
+        let children = doc.children(&root);
+        assert_eq!(&children[..3], &[img, b, text]);
+        assert_eq!(doc.text_content(&children[2]), "tail & ");
+        assert_eq!(doc.text_content(&children[3]), "World");
+        assert_eq!(doc.created(), vec![children[3]]);
+
+        // The raw html owns only its part of the split text
+        doc.clear_operations();
+        patch(&doc, &root, h!([h!(raw "<hr>"), h!("World")]), Some(node), &render_req);
+        assert_eq!(doc.removed(), vec![img, b, text]);
+    }
+
+    #[test]
+    fn should_infer_the_omitted_end_tags_of_the_raw_html_when_hydrating() {
+        let doc = MemoryDocument::new();
+        let root = doc.create_element("div");
+        let render_req = RenderRequest::new(|| {});
+        // As parsed by the browser, which closes the `<p>` and `<li>` left open
+        let mut nodes = doc.create_raw_html("<!-- <p> -->");
+        nodes.extend(["p", "p", "ul", "table", "li", "li"].iter().map(|tag| doc.create_element(tag)));
+        nodes.push(doc.create_text("World"));
+        for node in &nodes {
+            doc.append_child(&root, node);
+        }
+        doc.clear_operations();
+
+        let (_, mismatches) = hydrate(
+            h!([
+                h!(raw "<!-- <p> --><p>One<p>Two<ul><li>a<li>b</ul>"),
+                h!(raw "<table><tr><td>1<td>2</table><li>c<li>d"),
+                h!("World")
+            ]),
+            &doc, &root, SavedStates::new(), render_req.sender()
+        );
+        assert!(mismatches.is_empty());
+        assert_eq!(doc.children(&root), nodes);
+        assert!(doc.created().is_empty());
+    }
+
+    #[test]
+    fn should_report_and_patch_mismatches_when_hydrating() {
+        let doc = MemoryDocument::new();
+        let root = doc.create_element("div");
+        let render_req = RenderRequest::new(|| {});
+        patch(&doc, &root, h!("div", h!([h!("p", h!("Hi")), h!("span", _), h!("b", _)])), None, &render_req);
+
+        let (_, mismatches) = hydrate(h!("div", h!([h!("p", h!("Hello")), h!("em", _)])), &doc, &root, SavedStates::new(), render_req.sender());
+        assert_eq!(mismatches, vec![
+            Mismatch::Text { expected: "Hello".to_string(), found: "Hi".to_string() },
+            Mismatch::Node { expected: "<em>".to_string(), found: "<span>".to_string() },
+            Mismatch::Extra("<b>".to_string()),
+        ]);
+        assert_eq!(doc.inner_html(&root), "<div><p>Hello</p><em></em></div>");
+    }
+
+    #[test]
+    fn should_mount_components_when_hydrating() {
+        let doc = MemoryDocument::new();
+        let root = doc.create_element("div");
+        let render_req = RenderRequest::new(|| {});
+        let hooks = Rc::new(RefCell::new(vec![]));
+        let props = TrackedProps {
+            hooks: hooks.clone(),
+            skip_render: Rc::new(Cell::new(false)),
+            count: Rc::new(Cell::new(0)),
+            notifier: Rc::new(RefCell::new(None))
+        };
+
+        let (_, mismatches) = hydrate(VNode::Component(VComponent::new::<Tracked>(props)), &doc, &root, SavedStates::new(), render_req.sender());
+        assert_eq!(*hooks.borrow(), vec!["created", "before_mount", "render", "mounted"]);
+        assert_eq!(mismatches, vec![Mismatch::Missing("<span>".to_string())]);
+        assert_eq!(doc.inner_html(&root), "<span>0</span>");
+    }
+
+    /// Saves its count on the server, which is restored instead of the one given on the client.
+    struct Saved(u32);
+
+    impl Component for Saved {
+        type Props = u32;
+
+        fn create(props: Self::Props, _: Box<Fn()>) -> Self {
+            Saved(restored_state().map(|it| it.parse().unwrap()).unwrap_or(props))
+        }
+        fn update(&self, _: Self::Props) {}
+        fn eq_props(&self, _: &Self::Props) -> bool {
+            true
+        }
+        fn save_state(&self) -> Option<String> {
+            Some(self.0.to_string())
+        }
+    }
+
+    impl Lifecycle for Saved {}
+    impl Render for Saved {
+        fn render(&self) -> VNode {
+            h!("b", h!(self.0.to_string()))
+        }
+    }
+
+    fn saved_app(first: u32, second: u32) -> VNode {
+        h!("div", h!([
+            VNode::Component(VComponent::new::<Saved>(first)),
+            h!("p", VNode::Component(VComponent::new::<Saved>(second))),
+        ]))
+    }
+
+    #[cfg(feature = "save_state")]
+    #[test]
+    fn should_save_the_component_states_within_the_document() {
+        let mut document = Document::new(saved_app(1, 2), "app");
+        assert_eq!(document.render_to_string(), concat!(
+            r#"<!DOCTYPE html><html><head><meta charset="utf-8"></head>"#,
+            r#"<body><div id="app"><div><b>1</b><p><b>2</b></p></div></div>"#,
+            r#"<script>window.__papito_state__ = {"0.0.0":1,"0.0.1.0":2};</script></body></html>"#
+        ));
+    }
+
+    #[test]
+    fn should_stream_the_same_document_as_render_to_string() {
+        let document = || Document::new(saved_app(1, 2), "app")
+            .head("style", ("link", vec![("rel", "stylesheet"), ("href", "/main.css")]));
+        let mut html = vec![];
+        document().render_to_writer(&mut html).unwrap();
+        assert_eq!(String::from_utf8(html).unwrap(), document().render_to_string());
+    }
+
+    #[test]
+    fn should_restore_the_saved_component_states_when_hydrating() {
+        let mut server_app = saved_app(7, 8);
+        server_app.server_render();
+        let mut states = SavedStates::new();
+        server_app.collect_states(&mut vec![0], &mut states);
+        let saved: Vec<_> = states.iter().map(|(k, v)| (&**k, &**v)).collect();
+        assert_eq!(saved, vec![("0.0.0", "7"), ("0.0.1.0", "8")]);
+
+        let doc = MemoryDocument::new();
+        let root = doc.create_element("div");
+        let render_req = RenderRequest::new(|| {});
+        patch(&doc, &root, saved_app(7, 8), None, &render_req);
+        let (_, mismatches) = hydrate(saved_app(1, 2), &doc, &root, states, render_req.sender());
+        assert!(mismatches.is_empty());
+        assert_eq!(doc.inner_html(&root), "<div><b>7</b><p><b>8</b></p></div>");
+        assert!(restored_state().is_none());
+    }
+
+    #[cfg(feature = "save_state")]
+    #[test]
+    fn should_escape_the_saved_states_and_leave_out_the_ones_not_json() {
+        use state;
+        let mut states = SavedStates::new();
+        states.insert("0.0".to_string(), "\"</script><!--<script> & \u{2028}\"".to_string());
+        states.insert("0.1".to_string(), "alert(1)".to_string());
+        assert_eq!(
+            state::to_json(&states),
+            r#"{"0.0":"\u003c/script\u003e\u003c!--\u003cscript\u003e \u0026 \u2028"}"#
+        );
+    }
+
+    #[cfg(feature = "save_state")]
+    #[test]
+    fn should_serialize_the_saved_states_as_json() {
+        use state::{self, save_state, restore_state};
+        assert_eq!(save_state(&(&7, &"seven".to_string())), Some(r#"[7,"seven"]"#.to_string()));
+        let restored = state::restoring(Some(r#"[7,"seven"]"#.to_string()), restore_state::<(u32, String)>);
+        assert_eq!(restored, Some((7, "seven".to_string())));
+        assert_eq!(state::restoring(None, restore_state::<(u32, String)>), None);
+    }
+
+    type StubSource = Rc<RefCell<Option<&'static str>>>;
+
+    /// Stays pending for a few polls and then until the value is given to its `source`.
+    struct Stub {
+        source: StubSource,
+        pending_polls: u32,
+        target: Rc<RefCell<&'static str>>,
+    }
+
+    impl Future for Stub {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut TaskContext) -> Poll<()> {
+            if self.pending_polls > 0 {
+                self.pending_polls -= 1;
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            match self.source.borrow_mut().take() {
+                Some(value) => {
+                    *self.target.borrow_mut() = value;
+                    Poll::Ready(())
+                }
+                None => Poll::Pending
+            }
+        }
+    }
+
+    struct Loaded {
+        title: Rc<RefCell<&'static str>>,
+        load: RefCell<Option<Stub>>,
+        child: Option<StubSource>,
+    }
+
+    struct LoadedProps {
+        source: StubSource,
+        pending_polls: u32,
+        child: Option<StubSource>,
+    }
+
+    impl Component for Loaded {
+        type Props = LoadedProps;
+
+        fn create(props: Self::Props, _: Box<Fn()>) -> Self {
+            let title = Rc::new(RefCell::new(""));
+            Loaded {
+                title: title.clone(),
+                load: RefCell::new(Some(Stub {
+                    source: props.source,
+                    pending_polls: props.pending_polls,
+                    target: title,
+                })),
+                child: props.child,
+            }
+        }
+        fn update(&self, _: Self::Props) {}
+        fn eq_props(&self, _: &Self::Props) -> bool {
+            true
+        }
+    }
+
+    impl Lifecycle for Loaded {
+        fn load(&self) -> Option<Load> {
+            self.load.borrow_mut().take().map(|stub| Box::pin(stub) as Load)
+        }
+    }
+
+    impl Render for Loaded {
+        fn render(&self) -> VNode {
+            let title = h!("h1", h!(*self.title.borrow()));
+            match self.child {
+                Some(ref child) => h!("section", h!([title, loaded(child.clone(), 0, None)])),
+                None => h!("section", title)
+            }
+        }
+    }
+
+    fn loaded(source: StubSource, pending_polls: u32, child: Option<StubSource>) -> VNode {
+        VNode::Component(VComponent::new::<Loaded>(LoadedProps { source, pending_polls, child }))
+    }
+
+    fn stub_source(value: Option<&'static str>) -> StubSource {
+        Rc::new(RefCell::new(value))
+    }
+
+    #[test]
+    fn should_await_the_loads_before_rendering_on_the_server() {
+        let node = h!("div", h!([
+            loaded(stub_source(Some("A")), 2, None),
+            loaded(stub_source(Some("B")), 0, Some(stub_source(Some("C")))),
+        ]));
+        assert_eq!(
+            block_on(node.render_to_string_async()),
+            "<div><section><h1>A</h1></section><section><h1>B</h1><section><h1>C</h1></section></section></div>"
+        );
+    }
+
+    struct NoopWaker;
+
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    #[test]
+    fn should_load_the_children_once_the_parent_is_loaded() {
+        let parent = stub_source(None);
+        let child = stub_source(None);
+        let mut future = loaded(parent.clone(), 0, Some(child.clone())).render_to_string_async();
+        let waker = Waker::from(Arc::new(NoopWaker));
+        let mut cx = TaskContext::from_waker(&waker);
+        assert!(Pin::new(&mut future).poll(&mut cx).is_pending());
+
+        *parent.borrow_mut() = Some("Parent");
+        assert!(Pin::new(&mut future).poll(&mut cx).is_pending());
+
+        *child.borrow_mut() = Some("Child");
+        assert_eq!(
+            Pin::new(&mut future).poll(&mut cx),
+            Poll::Ready("<section><h1>Parent</h1><section><h1>Child</h1></section></section>".to_string())
+        );
+    }
+
+    #[test]
+    fn should_await_the_loads_of_a_document() {
+        let document = Document::new(loaded(stub_source(Some("Loaded")), 1, None), "app");
+        assert_eq!(
+            block_on(document.render_to_string_async()),
+            concat!(
+                r#"<!DOCTYPE html><html><head><meta charset="utf-8"></head>"#,
+                r#"<body><div id="app"><section><h1>Loaded</h1></section></div></body></html>"#
+            )
+        );
     }
 }
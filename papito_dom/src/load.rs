@@ -0,0 +1,56 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context as TaskContext, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+use traits::ServerLoad;
+
+/// Renders the html once the loads of all the components of the tree are resolved.
+pub struct ServerRenderFuture<T> {
+    tree: Option<T>,
+}
+
+impl<T> ServerRenderFuture<T> {
+    pub fn new(tree: T) -> ServerRenderFuture<T> {
+        ServerRenderFuture {
+            tree: Some(tree),
+        }
+    }
+}
+
+impl<T: ServerLoad + ToString + Unpin> Future for ServerRenderFuture<T> {
+    type Output = String;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut TaskContext) -> Poll<String> {
+        let this = self.get_mut();
+        let loaded = this.tree.as_mut()
+            .expect("The future is already resolved")
+            .poll_load(cx);
+        match loaded {
+            Poll::Ready(()) => Poll::Ready(this.tree.take().unwrap().to_string()),
+            Poll::Pending => Poll::Pending
+        }
+    }
+}
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Runs the `future` to completion on the current thread. A minimal local executor for the async
+/// server render, where the server does not run one of its own.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = Box::pin(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = TaskContext::from_waker(&waker);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+        thread::park();
+    }
+}
@@ -0,0 +1,52 @@
+use vnode::VNode;
+
+/// The namespace an element is created within.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Namespace {
+    Html,
+    Svg,
+    MathMl,
+}
+
+impl Namespace {
+    pub fn uri(&self) -> &'static str {
+        match *self {
+            Namespace::Html => "http://www.w3.org/1999/xhtml",
+            Namespace::Svg => "http://www.w3.org/2000/svg",
+            Namespace::MathMl => "http://www.w3.org/1998/Math/MathML",
+        }
+    }
+
+    pub fn from_uri(uri: &str) -> Option<Namespace> {
+        [Namespace::Html, Namespace::Svg, Namespace::MathMl].iter()
+            .find(|it| it.uri() == uri)
+            .cloned()
+    }
+
+    /// The namespace that is started by the `tag` itself, i.e. `svg` and `math`.
+    pub fn of_tag(tag: &str) -> Option<Namespace> {
+        if tag.eq_ignore_ascii_case("svg") {
+            Some(Namespace::Svg)
+        } else if tag.eq_ignore_ascii_case("math") {
+            Some(Namespace::MathMl)
+        } else {
+            None
+        }
+    }
+}
+
+/// Passes the namespace of an element down to its descendants, unless overridden by them.
+pub trait InheritNamespace {
+    fn inherit_namespace(&mut self, ns: Namespace);
+}
+
+impl InheritNamespace for VNode {
+    fn inherit_namespace(&mut self, ns: Namespace) {
+        match *self {
+            VNode::Element(ref mut element) => element.inherit_namespace(ns),
+            VNode::List(ref mut list) => list.inherit_namespace(ns),
+            VNode::Component(ref mut component) => component.inherit_namespace(ns),
+            VNode::Text(_) | VNode::Raw(_) => {}
+        }
+    }
+}
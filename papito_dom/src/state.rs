@@ -0,0 +1,134 @@
+use std::cell::RefCell;
+#[cfg(not(target_arch = "wasm32"))]
+use std::mem;
+use indexmap::IndexMap;
+#[cfg(feature = "save_state")]
+use serde::Serialize;
+#[cfg(feature = "save_state")]
+use serde::de::DeserializeOwned;
+#[cfg(feature = "save_state")]
+use serde_json::{self, Map, Value};
+#[cfg(feature = "save_state")]
+use validate;
+
+/// The states saved by the components rendered on the server, keyed by their position in the tree.
+pub type SavedStates = IndexMap<String, String>;
+
+thread_local! {
+    static RESTORED: RefCell<Option<String>> = const { RefCell::new(None) };
+    /// The position of the vnode being streamed, along with the states saved so far.
+    #[cfg(not(target_arch = "wasm32"))]
+    static STREAMED: RefCell<Option<(Vec<usize>, SavedStates)>> = const { RefCell::new(None) };
+}
+
+/// The state saved on the server by the component being created, while it is hydrated. It can
+/// only be taken once.
+pub fn restored_state() -> Option<String> {
+    RESTORED.with(|it| it.borrow_mut().take())
+}
+
+/// Runs `f` with the `state` to be restored by the component created within.
+pub fn restoring<F, R>(state: Option<String>, f: F) -> R where
+    F: FnOnce() -> R {
+    RESTORED.with(|it| *it.borrow_mut() = state);
+    let result = f();
+    RESTORED.with(|it| *it.borrow_mut() = None);
+    result
+}
+
+/// The key of the component at the `path`, which is the position of each of its ancestors among
+/// their siblings, e.g. `0.2.0`.
+pub fn position_key(path: &[usize]) -> String {
+    path.iter()
+        .map(|it| it.to_string())
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Runs `f` while collecting the states saved by the components streamed within. They are saved
+/// as they are streamed, since the streamed tree is not kept to collect them afterwards.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn collect_streamed<F, R>(f: F) -> (R, SavedStates) where
+    F: FnOnce() -> R {
+    let outer = STREAMED.with(|it| it.borrow_mut().replace((vec![0], SavedStates::new())));
+    let result = f();
+    let (_, states) = STREAMED.with(|it| mem::replace(&mut *it.borrow_mut(), outer))
+        .expect("Impossible. The states were set above");
+    (result, states)
+}
+
+/// Runs `f` which streams the child at the `index` of the vnode being streamed.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn streaming_child<F, R>(index: usize, f: F) -> R where
+    F: FnOnce() -> R {
+    with_streamed(|path, _| path.push(index));
+    let result = f();
+    with_streamed(|path, _| {
+        path.pop();
+    });
+    result
+}
+
+/// Saves the state of the component being streamed, only when the states are being collected.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_streamed<F>(state: F) where
+    F: FnOnce() -> Option<String> {
+    with_streamed(|path, states| {
+        if let Some(state) = state() {
+            states.insert(position_key(path), state);
+        }
+    });
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn with_streamed<F>(f: F) where
+    F: FnOnce(&mut Vec<usize>, &mut SavedStates) {
+    STREAMED.with(|it| {
+        if let Some((ref mut path, ref mut states)) = *it.borrow_mut() {
+            f(path, states);
+        }
+    });
+}
+
+/// The saved states as a json object, which is safe to be inlined within a `<script>`. A state
+/// which is not a json value is left out.
+#[cfg(feature = "save_state")]
+pub fn to_json(states: &SavedStates) -> String {
+    let object: Map<String, Value> = states.iter()
+        .filter_map(|(key, state)| match serde_json::from_str(state) {
+            Ok(value) => Some((key.clone(), value)),
+            Err(_) => {
+                validate::warn(&format!("The state saved by the component at `{}` is not json", key));
+                None
+            }
+        })
+        .collect();
+    escape_for_script(&Value::Object(object).to_string())
+}
+
+/// Escapes the characters of the `json` which may close the script or start a comment within it,
+/// along with the line separators which are invalid in older js. They only occur within strings,
+/// where they are escaped as unicode.
+#[cfg(feature = "save_state")]
+fn escape_for_script(json: &str) -> String {
+    let mut escaped = String::with_capacity(json.len());
+    for ch in json.chars() {
+        match ch {
+            '<' | '>' | '&' | '\u{2028}' | '\u{2029}' => escaped.push_str(&format!("\\u{:04x}", ch as u32)),
+            _ => escaped.push(ch)
+        }
+    }
+    escaped
+}
+
+/// Serializes the state of a component as json.
+#[cfg(feature = "save_state")]
+pub fn save_state<T: Serialize>(state: &T) -> Option<String> {
+    serde_json::to_string(state).ok()
+}
+
+/// Deserializes the state saved on the server by the component being created, if any.
+#[cfg(feature = "save_state")]
+pub fn restore_state<T: DeserializeOwned>() -> Option<T> {
+    restored_state().and_then(|it| serde_json::from_str(&it).ok())
+}
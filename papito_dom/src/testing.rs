@@ -0,0 +1,427 @@
+use std::rc::Rc;
+use std::cell::RefCell;
+use indexmap::IndexMap;
+use backend::Backend;
+use events::DOMEvent;
+use escape::Escaped;
+use velement::{is_void_element, is_raw_text_element, AttrValue, ClassList, Style};
+
+/// A handle to a node within a `MemoryDocument`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct MemoryNode(usize);
+
+/// A mutation done on a `MemoryDocument` by the patch engine.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Operation {
+    Created(MemoryNode),
+    Inserted(MemoryNode),
+    Moved(MemoryNode),
+    Removed(MemoryNode),
+    TextSet(MemoryNode, String),
+    AttributeSet(MemoryNode, String, String),
+    AttributeRemoved(MemoryNode, String),
+    ClassAdded(MemoryNode, String),
+    ClassRemoved(MemoryNode, String),
+    StyleSet(MemoryNode, String, String),
+    StyleRemoved(MemoryNode, String),
+    PropertySet(MemoryNode, String, AttrValue),
+    ListenerAdded(MemoryNode, &'static str),
+    ListenerRemoved(MemoryNode, &'static str),
+}
+
+#[derive(Debug)]
+enum NodeKind {
+    Element {
+        tag: String,
+        namespace: Option<String>,
+        attrs: IndexMap<String, String>,
+        props: IndexMap<String, AttrValue>,
+        listeners: Vec<&'static str>,
+    },
+    Text(String),
+    Raw(String),
+}
+
+#[derive(Debug)]
+struct NodeData {
+    kind: NodeKind,
+    parent: Option<MemoryNode>,
+    children: Vec<MemoryNode>,
+}
+
+/// An in-memory DOM used as a `Backend` on non-browser hosts. Along with the node tree, it
+/// records a log of every `Operation` done on it, so that the patches can be asserted upon.
+/// Cloning the document only clones the handle, all the clones share the same tree.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryDocument {
+    nodes: Rc<RefCell<Vec<NodeData>>>,
+    operations: Rc<RefCell<Vec<Operation>>>,
+}
+
+impl MemoryDocument {
+    pub fn new() -> MemoryDocument {
+        MemoryDocument::default()
+    }
+
+    fn add_node(&self, kind: NodeKind) -> MemoryNode {
+        let node = {
+            let mut nodes = self.nodes.borrow_mut();
+            nodes.push(NodeData {
+                kind,
+                parent: None,
+                children: vec![],
+            });
+            MemoryNode(nodes.len() - 1)
+        };
+        self.log(Operation::Created(node));
+        node
+    }
+
+    fn log(&self, operation: Operation) {
+        self.operations.borrow_mut().push(operation);
+    }
+
+    /// Logs an insertion of the `child` or a move if it was already attached.
+    fn log_insertion(&self, child: &MemoryNode) {
+        if self.parent(child).is_some() {
+            self.log(Operation::Moved(*child));
+        } else {
+            self.log(Operation::Inserted(*child));
+        }
+    }
+
+    /// All the operations done on the document in order.
+    pub fn operations(&self) -> Vec<Operation> {
+        self.operations.borrow().clone()
+    }
+
+    /// Clears the operation log, so that only the operations done by the following patches
+    /// are recorded.
+    pub fn clear_operations(&self) {
+        self.operations.borrow_mut().clear();
+    }
+
+    /// The nodes created since the log was last cleared.
+    pub fn created(&self) -> Vec<MemoryNode> {
+        self.filter_operations(|op| if let Operation::Created(node) = *op { Some(node) } else { None })
+    }
+
+    /// The nodes inserted since the log was last cleared. Does not include moved nodes.
+    pub fn inserted(&self) -> Vec<MemoryNode> {
+        self.filter_operations(|op| if let Operation::Inserted(node) = *op { Some(node) } else { None })
+    }
+
+    /// The already attached nodes that were moved since the log was last cleared.
+    pub fn moved(&self) -> Vec<MemoryNode> {
+        self.filter_operations(|op| if let Operation::Moved(node) = *op { Some(node) } else { None })
+    }
+
+    /// The nodes removed since the log was last cleared.
+    pub fn removed(&self) -> Vec<MemoryNode> {
+        self.filter_operations(|op| if let Operation::Removed(node) = *op { Some(node) } else { None })
+    }
+
+    fn filter_operations<F>(&self, filter: F) -> Vec<MemoryNode> where
+        F: Fn(&Operation) -> Option<MemoryNode> {
+        self.operations.borrow().iter()
+            .filter_map(filter)
+            .collect()
+    }
+
+    /// Updates the attribute `name` without logging it. It is removed once it is empty.
+    fn update_attribute<F>(&self, node: &MemoryNode, name: &str, update: F) where
+        F: FnOnce(&str) -> String {
+        let mut nodes = self.nodes.borrow_mut();
+        match nodes[node.0].kind {
+            NodeKind::Element { ref mut attrs, .. } => {
+                let value = update(attrs.get(name).map(|it| it.as_str()).unwrap_or(""));
+                if value.is_empty() {
+                    attrs.retain(|k, _| k != name);
+                } else {
+                    attrs.insert(name.to_string(), value);
+                }
+            }
+            _ => panic!("Cannot set an attribute on a non-element node")
+        }
+    }
+
+    fn detach(&self, child: &MemoryNode) {
+        let mut nodes = self.nodes.borrow_mut();
+        if let Some(parent) = nodes[child.0].parent.take() {
+            nodes[parent.0].children.retain(|it| it != child);
+        }
+    }
+
+    /// The parent of the `node` if it is attached.
+    pub fn parent(&self, node: &MemoryNode) -> Option<MemoryNode> {
+        self.nodes.borrow()[node.0].parent
+    }
+
+    /// The children of the `node` in order.
+    pub fn children(&self, node: &MemoryNode) -> Vec<MemoryNode> {
+        self.nodes.borrow()[node.0].children.clone()
+    }
+
+    /// The tag name of the `node` if it is an element.
+    pub fn tag(&self, node: &MemoryNode) -> Option<String> {
+        match self.nodes.borrow()[node.0].kind {
+            NodeKind::Element { ref tag, .. } => Some(tag.clone()),
+            _ => None
+        }
+    }
+
+    /// The namespace uri of the `node` if it is an element created within a namespace.
+    pub fn namespace(&self, node: &MemoryNode) -> Option<String> {
+        match self.nodes.borrow()[node.0].kind {
+            NodeKind::Element { ref namespace, .. } => namespace.clone(),
+            _ => None
+        }
+    }
+
+    /// The value of the attribute `name` on the `node`.
+    pub fn attribute(&self, node: &MemoryNode, name: &str) -> Option<String> {
+        match self.nodes.borrow()[node.0].kind {
+            NodeKind::Element { ref attrs, .. } => attrs.get(name).cloned(),
+            _ => None
+        }
+    }
+
+    /// The live value of the property `name` on the `node`. They are not reflected as attributes.
+    pub fn property(&self, node: &MemoryNode, name: &str) -> Option<AttrValue> {
+        match self.nodes.borrow()[node.0].kind {
+            NodeKind::Element { ref props, .. } => props.get(name).cloned(),
+            _ => None
+        }
+    }
+
+    /// The event types that are currently listened to on the `node`.
+    pub fn listeners(&self, node: &MemoryNode) -> Vec<&'static str> {
+        match self.nodes.borrow()[node.0].kind {
+            NodeKind::Element { ref listeners, .. } => listeners.clone(),
+            _ => vec![]
+        }
+    }
+
+    /// The text content of the `node` along with its descendants.
+    pub fn text_content(&self, node: &MemoryNode) -> String {
+        let nodes = self.nodes.borrow();
+        match nodes[node.0].kind {
+            NodeKind::Text(ref content) | NodeKind::Raw(ref content) => content.clone(),
+            NodeKind::Element { .. } => {
+                drop(nodes);
+                self.children(node).iter()
+                    .map(|it| self.text_content(it))
+                    .collect()
+            }
+        }
+    }
+
+    /// Serializes the `node` along with its descendants as html.
+    pub fn outer_html(&self, node: &MemoryNode) -> String {
+        let nodes = self.nodes.borrow();
+        match nodes[node.0].kind {
+            NodeKind::Text(ref content) => Escaped(content).to_string(),
+            NodeKind::Raw(ref html) => html.clone(),
+            NodeKind::Element { ref tag, ref namespace, ref attrs, .. } => {
+                let mut html = format!("<{}", tag);
+                for (k, v) in attrs.iter() {
+                    html.push_str(&format!(" {}=\"{}\"", k, Escaped(v)));
+                }
+                html.push('>');
+                if namespace.is_none() && is_void_element(tag) {
+                    return html;
+                }
+                let tag = tag.clone();
+                let has_raw_text = namespace.is_none() && is_raw_text_element(&tag);
+                drop(nodes);
+                if has_raw_text {
+                    html.push_str(&self.text_content(node));
+                } else {
+                    html.push_str(&self.inner_html(node));
+                }
+                html.push_str(&format!("</{}>", tag));
+                html
+            }
+        }
+    }
+
+    /// Serializes the descendants of the `node` as html.
+    pub fn inner_html(&self, node: &MemoryNode) -> String {
+        self.children(node).iter()
+            .map(|it| self.outer_html(it))
+            .collect()
+    }
+}
+
+impl Backend for MemoryDocument {
+    type Node = MemoryNode;
+
+    fn create_element(&self, tag: &str) -> MemoryNode {
+        self.add_node(NodeKind::Element {
+            tag: tag.to_string(),
+            namespace: None,
+            attrs: IndexMap::new(),
+            props: IndexMap::new(),
+            listeners: vec![],
+        })
+    }
+
+    fn create_element_ns(&self, namespace: &str, tag: &str) -> MemoryNode {
+        self.add_node(NodeKind::Element {
+            tag: tag.to_string(),
+            namespace: Some(namespace.to_string()),
+            attrs: IndexMap::new(),
+            props: IndexMap::new(),
+            listeners: vec![],
+        })
+    }
+
+    fn create_text(&self, content: &str) -> MemoryNode {
+        self.add_node(NodeKind::Text(content.to_string()))
+    }
+
+    /// The raw html is not parsed, rather kept as a single opaque node that is serialized as is.
+    /// So the nodes of a raw html rendered by the server are to be built by hand to hydrate it.
+    fn create_raw_html(&self, html: &str) -> Vec<MemoryNode> {
+        vec![self.add_node(NodeKind::Raw(html.to_string()))]
+    }
+
+    fn set_text_content(&self, node: &MemoryNode, content: &str) {
+        let mut nodes = self.nodes.borrow_mut();
+        match nodes[node.0].kind {
+            NodeKind::Text(ref mut text) => *text = content.to_string(),
+            _ => panic!("Cannot set the text content of a non-text node")
+        }
+        self.log(Operation::TextSet(*node, content.to_string()));
+    }
+
+    fn insert_before(&self, parent: &MemoryNode, child: &MemoryNode, next: &MemoryNode) {
+        self.log_insertion(child);
+        self.detach(child);
+        let mut nodes = self.nodes.borrow_mut();
+        let pos = nodes[parent.0].children.iter().position(|it| it == next)
+            .expect("The reference node is not a child of the parent");
+        nodes[parent.0].children.insert(pos, *child);
+        nodes[child.0].parent = Some(*parent);
+    }
+
+    fn append_child(&self, parent: &MemoryNode, child: &MemoryNode) {
+        self.log_insertion(child);
+        self.detach(child);
+        let mut nodes = self.nodes.borrow_mut();
+        nodes[parent.0].children.push(*child);
+        nodes[child.0].parent = Some(*parent);
+    }
+
+    fn remove_child(&self, parent: &MemoryNode, child: &MemoryNode) {
+        assert_eq!(self.parent(child).as_ref(), Some(parent), "The node is not a child of the parent");
+        self.detach(child);
+        self.log(Operation::Removed(*child));
+    }
+
+    fn set_attribute(&self, node: &MemoryNode, name: &str, value: &str) {
+        let mut nodes = self.nodes.borrow_mut();
+        match nodes[node.0].kind {
+            NodeKind::Element { ref mut attrs, .. } => {
+                attrs.insert(name.to_string(), value.to_string());
+            }
+            _ => panic!("Cannot set an attribute on a non-element node")
+        }
+        self.log(Operation::AttributeSet(*node, name.to_string(), value.to_string()));
+    }
+
+    fn remove_attribute(&self, node: &MemoryNode, name: &str) {
+        let mut nodes = self.nodes.borrow_mut();
+        if let NodeKind::Element { ref mut attrs, .. } = nodes[node.0].kind {
+            attrs.retain(|k, _| k != name);
+        }
+        self.log(Operation::AttributeRemoved(*node, name.to_string()));
+    }
+
+    /// Kept within the `class` attribute, the same as a browser reflects the class list.
+    fn add_class(&self, node: &MemoryNode, class: &str) {
+        self.update_attribute(node, "class", |classes| {
+            let mut classes = ClassList::from(classes.to_string());
+            classes.add(class.to_string());
+            classes.to_string()
+        });
+        self.log(Operation::ClassAdded(*node, class.to_string()));
+    }
+
+    fn remove_class(&self, node: &MemoryNode, class: &str) {
+        self.update_attribute(node, "class", |classes| {
+            let mut classes = ClassList::from(classes.to_string());
+            classes.remove(class);
+            classes.to_string()
+        });
+        self.log(Operation::ClassRemoved(*node, class.to_string()));
+    }
+
+    /// Kept within the `style` attribute, the same as a browser reflects the inline style.
+    fn set_style(&self, node: &MemoryNode, property: &str, value: &str) {
+        self.update_attribute(node, "style", |style| {
+            let mut style = Style::from(style.to_string());
+            style.set(property.to_string(), value.to_string());
+            style.to_string()
+        });
+        self.log(Operation::StyleSet(*node, property.to_string(), value.to_string()));
+    }
+
+    fn remove_style(&self, node: &MemoryNode, property: &str) {
+        self.update_attribute(node, "style", |style| {
+            let mut style = Style::from(style.to_string());
+            style.remove(property);
+            style.to_string()
+        });
+        self.log(Operation::StyleRemoved(*node, property.to_string()));
+    }
+
+    fn set_property(&self, node: &MemoryNode, name: &str, value: &AttrValue) {
+        let mut nodes = self.nodes.borrow_mut();
+        match nodes[node.0].kind {
+            NodeKind::Element { ref mut props, .. } => {
+                if let AttrValue::Absent = *value {
+                    props.retain(|k, _| k != name);
+                } else {
+                    props.insert(name.to_string(), value.clone());
+                }
+            }
+            _ => panic!("Cannot set a property on a non-element node")
+        }
+        self.log(Operation::PropertySet(*node, name.to_string(), value.clone()));
+    }
+
+    fn add_listener(&self, node: &MemoryNode, listener: &mut DOMEvent) {
+        let mut nodes = self.nodes.borrow_mut();
+        if let NodeKind::Element { ref mut listeners, .. } = nodes[node.0].kind {
+            listeners.push(listener.event_type());
+        }
+        self.log(Operation::ListenerAdded(*node, listener.event_type()));
+    }
+
+    fn remove_listener(&self, node: &MemoryNode, listener: &mut DOMEvent) {
+        let mut nodes = self.nodes.borrow_mut();
+        if let NodeKind::Element { ref mut listeners, .. } = nodes[node.0].kind {
+            let event_type = listener.event_type();
+            if let Some(pos) = listeners.iter().position(|it| *it == event_type) {
+                listeners.remove(pos);
+            }
+        }
+        self.log(Operation::ListenerRemoved(*node, listener.event_type()));
+    }
+
+    fn child_nodes(&self, node: &MemoryNode) -> Vec<MemoryNode> {
+        self.children(node)
+    }
+
+    fn tag_name(&self, node: &MemoryNode) -> Option<String> {
+        self.tag(node)
+    }
+
+    fn text(&self, node: &MemoryNode) -> Option<String> {
+        match self.nodes.borrow()[node.0].kind {
+            NodeKind::Text(ref content) => Some(content.clone()),
+            _ => None
+        }
+    }
+}
@@ -1,13 +1,20 @@
 use vnode::VNode;
-#[cfg(target_arch = "wasm32")]
-use stdweb::web::{Element, Node};
-#[cfg(target_arch = "wasm32")]
 use events::RenderRequestSender;
+use backend::Backend;
 use std::any::Any;
+use std::future::Future;
+use std::pin::Pin;
+#[cfg(not(target_arch = "wasm32"))]
+use std::task::{Context as TaskContext, Poll};
+#[cfg(not(target_arch = "wasm32"))]
+use load::ServerRenderFuture;
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::{self, Write};
+#[cfg(not(target_arch = "wasm32"))]
+use state::SavedStates;
 
-#[cfg(target_arch = "wasm32")]
 pub trait DOMRender {
-    fn dom_render(&mut self, parent: &Element, next: Option<&Node>, render_req: RenderRequestSender);
+    fn dom_render<B: Backend>(&mut self, backend: &B, parent: &B::Node, next: Option<&B::Node>, render_req: RenderRequestSender);
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -20,23 +27,84 @@ pub trait RenderToString {
     fn render_to_string(&mut self) -> String;
 }
 
+/// Writes the html as the tree is rendered. A component is only rendered once it is reached and
+/// its rendered tree is dropped as soon as it is written.
+#[cfg(not(target_arch = "wasm32"))]
+pub trait ServerStream {
+    fn server_stream(self, writer: &mut Write) -> io::Result<()>;
+}
+
+/// Collects the states saved by the components of the rendered tree, keyed by their position.
+#[cfg(not(target_arch = "wasm32"))]
+pub trait CollectStates {
+    fn collect_states(&self, path: &mut Vec<usize>, states: &mut SavedStates);
+}
+
+/// Polls the loads of the components, rendering each one as soon as its load is resolved. It is
+/// ready once every component of the tree is rendered.
+#[cfg(not(target_arch = "wasm32"))]
+pub trait ServerLoad {
+    fn poll_load(&mut self, cx: &mut TaskContext) -> Poll<()>;
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub trait RenderToStringAsync: Sized {
+    fn render_to_string_async(self) -> ServerRenderFuture<Self>;
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub trait RenderToWriter {
+    fn render_to_writer<W: Write>(self, writer: &mut W) -> io::Result<()>;
+}
+
 pub trait Component: Lifecycle {
     type Props;
 
+    /// The names of the props which cannot be left out with `..`.
+    const REQUIRED_PROPS: &'static [&'static str] = &[];
+
     fn create(props: Self::Props, notifier: Box<Fn()>) -> Self;
 
     fn update(&self, props: Self::Props);
 
     fn eq_props(&self, rhs: &Self::Props) -> bool;
+
+    /// The state to be handed off from the server to the client. It must be a json value, as it
+    /// is inlined within the document as such. It is restored on the client, via `restored_state`
+    /// within `create`, when the markup is hydrated. The states are only inlined with the
+    /// `save_state` feature.
+    fn save_state(&self) -> Option<String> {
+        None
+    }
 }
 
+/// The data loaded by a component before it is first rendered on the server.
+pub type Load = Pin<Box<Future<Output = ()>>>;
+
 pub trait Lifecycle: Render + AsAny {
     fn created(&self) {}
 
+    /// Loads the data required by the first render, e.g. by setting the states once it is
+    /// fetched. Only awaited by the async server render.
+    fn load(&self) -> Option<Load> {
+        None
+    }
+
+    fn before_mount(&self) {}
+
     fn mounted(&self) {}
 
+    /// Whether the changed component is to be re-rendered. It is always rendered the first time.
+    fn should_render(&self) -> bool {
+        true
+    }
+
+    fn before_update(&self) {}
+
     fn updated(&self) {}
 
+    fn before_destroy(&self) {}
+
     fn destroyed(&self) {}
 }
 
@@ -61,3 +129,17 @@ impl<T: ServerRender + ToString> RenderToString for T {
         self.to_string()
     }
 }
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<T: ServerLoad + ToString + Unpin> RenderToStringAsync for T {
+    fn render_to_string_async(self) -> ServerRenderFuture<Self> {
+        ServerRenderFuture::new(self)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<T: ServerStream> RenderToWriter for T {
+    fn render_to_writer<W: Write>(self, writer: &mut W) -> io::Result<()> {
+        self.server_stream(writer)
+    }
+}
@@ -0,0 +1,132 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
+use std::rc::Rc;
+use std::error::Error;
+use std::fmt::Display;
+use std::fmt::{Formatter, self};
+
+/// The reason an element could not be created.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ValidationError {
+    InvalidTagName(String),
+    InvalidAttributeName(String),
+    VoidElementWithChildren(String),
+    EndTagWithinRawText(String),
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            ValidationError::InvalidTagName(ref tag) => write!(f, "`{}` is not a valid tag name", tag),
+            ValidationError::InvalidAttributeName(ref name) => write!(f, "`{}` is not a valid attribute name", name),
+            ValidationError::VoidElementWithChildren(ref tag) => write!(f, "The void element `{}` cannot have any children", tag),
+            ValidationError::EndTagWithinRawText(ref tag) => write!(f, "The text within `{0}` cannot contain `</{0}`", tag),
+        }
+    }
+}
+
+impl Error for ValidationError {
+    fn description(&self) -> &str {
+        match *self {
+            ValidationError::InvalidTagName(_) => "invalid tag name",
+            ValidationError::InvalidAttributeName(_) => "invalid attribute name",
+            ValidationError::VoidElementWithChildren(_) => "void element with children",
+            ValidationError::EndTagWithinRawText(_) => "end tag within raw text",
+        }
+    }
+}
+
+/// A tag name starts with an ascii letter and may not contain whitespace, `/` or `>`.
+pub fn validate_tag_name(tag: &str) -> Result<(), ValidationError> {
+    let starts_with_letter = tag.chars().next().map(|it| it.is_ascii_alphabetic()).unwrap_or(false);
+    let has_invalid_char = tag.chars().any(|it| it.is_whitespace() || it.is_control() || it == '/' || it == '>');
+    if !starts_with_letter || has_invalid_char {
+        Err(ValidationError::InvalidTagName(tag.to_string()))
+    } else {
+        Ok(())
+    }
+}
+
+/// An attribute name is non-empty and may not contain whitespace, quotes, `/`, `>` or `=`.
+pub fn validate_attribute_name(name: &str) -> Result<(), ValidationError> {
+    let has_invalid_char = name.chars().any(|it| {
+        it.is_whitespace() || it.is_control() || ['"', '\'', '/', '>', '='].contains(&it)
+    });
+    if name.is_empty() || has_invalid_char {
+        Err(ValidationError::InvalidAttributeName(name.to_string()))
+    } else {
+        Ok(())
+    }
+}
+
+/// The elements defined by the html standard, including the obsolete ones still in use.
+const HTML_TAGS: &[&str] = &[
+    "a", "abbr", "address", "area", "article", "aside", "audio", "b", "base", "bdi", "bdo",
+    "blockquote", "body", "br", "button", "canvas", "caption", "cite", "code", "col", "colgroup",
+    "data", "datalist", "dd", "del", "details", "dfn", "dialog", "div", "dl", "dt", "em", "embed",
+    "fieldset", "figcaption", "figure", "footer", "form", "h1", "h2", "h3", "h4", "h5", "h6",
+    "head", "header", "hgroup", "hr", "html", "i", "iframe", "img", "input", "ins", "kbd",
+    "keygen", "label", "legend", "li", "link", "main", "map", "mark", "menu", "meta", "meter",
+    "nav", "noscript", "object", "ol", "optgroup", "option", "output", "p", "param", "picture",
+    "pre", "progress", "q", "rp", "rt", "ruby", "s", "samp", "script", "section", "select", "slot",
+    "small", "source", "span", "strong", "style", "sub", "summary", "sup", "table", "tbody", "td",
+    "template", "textarea", "tfoot", "th", "thead", "time", "title", "tr", "track", "u", "ul",
+    "var", "video", "wbr"
+];
+
+pub fn is_known_html_tag(tag: &str) -> bool {
+    HTML_TAGS.iter().any(|it| it.eq_ignore_ascii_case(tag))
+}
+
+thread_local! {
+    static STRICT_MODE: Cell<bool> = const { Cell::new(false) };
+    static WARNED_TAGS: RefCell<HashSet<String>> = RefCell::new(HashSet::new());
+    static WARNING_HANDLER: RefCell<Option<WarningHandler>> = const { RefCell::new(None) };
+}
+
+type WarningHandler = Rc<Fn(&str)>;
+
+/// Enables warnings for the html elements with an unknown tag when they are rendered. Meant for
+/// apps that do not use custom elements, where an unknown tag is most likely a typo.
+pub fn set_strict_mode(strict: bool) {
+    STRICT_MODE.with(|it| it.set(strict));
+}
+
+pub fn is_strict_mode() -> bool {
+    STRICT_MODE.with(|it| it.get())
+}
+
+/// Warns about an unknown html `tag` only the first time it is rendered, rather than on every
+/// render of it.
+pub fn warn_unknown_tag(tag: &str) {
+    let is_first = WARNED_TAGS.with(|it| it.borrow_mut().insert(tag.to_ascii_lowercase()));
+    if is_first {
+        warn(&format!("`{}` is not a known html tag", tag));
+    }
+}
+
+/// Passes the warnings, such as the unknown tags in the strict mode or the mismatches when
+/// hydrating, to the `handler`. Without one, they are logged to the console in the browser and
+/// dropped on the server.
+pub fn set_warning_handler<F: Fn(&str) + 'static>(handler: F) {
+    WARNING_HANDLER.with(|it| *it.borrow_mut() = Some(Rc::new(handler)));
+}
+
+pub fn warn(message: &str) {
+    // Taken out, so that the handler may itself set another one
+    let handler = WARNING_HANDLER.with(|it| it.borrow().clone());
+    match handler {
+        Some(handler) => handler(message),
+        None => log_warning(message)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn log_warning(message: &str) {
+    js! { @(no_return)
+        console.warn(@{message});
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn log_warning(_: &str) {}
@@ -8,130 +8,168 @@ use std::cell::RefCell;
 use traits::Component;
 use traits::Lifecycle;
 #[cfg(not(target_arch = "wasm32"))]
-use traits::ServerRender;
-#[cfg(target_arch = "wasm32")]
+use traits::{ServerRender, ServerStream, CollectStates, ServerLoad};
+#[cfg(not(target_arch = "wasm32"))]
+use traits::Load;
+#[cfg(not(target_arch = "wasm32"))]
+use std::task::{Context as TaskContext, Poll};
+#[cfg(not(target_arch = "wasm32"))]
+use state::{self, SavedStates};
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::{self, Write};
 use events::RenderRequestSender;
+use namespace::{Namespace, InheritNamespace};
+use context::{self, Provided};
 use std::mem;
+use std::any::Any;
 
 struct Props;
 
+type Initializer = Box<Fn(*mut Props, Rc<Fn()>) -> Box<Lifecycle>>;
+type PropsSetter = Box<Fn(&mut Box<Lifecycle>, *mut Props)>;
+/// Binds the listeners of a parent onto the callbacks of the instance, or unbinds them.
+type Binding = Box<Fn(&Any, bool)>;
+#[cfg(not(target_arch = "wasm32"))]
+type StateSaver = Box<Fn(&Any) -> Option<String>>;
+
 pub struct VComponent {
     type_id: TypeId,
     instance: Option<Box<Lifecycle>>,
     props: Option<*mut Props>,
-    #[cfg(target_arch = "wasm32")]
-    initializer: Box<Fn(*mut Props, RenderRequestSender) -> Box<Lifecycle>>,
+    initializer: Initializer,
+    props_setter: PropsSetter,
     #[cfg(not(target_arch = "wasm32"))]
-    initializer: Box<Fn(*mut Props) -> Box<Lifecycle>>,
-    #[cfg(target_arch = "wasm32")]
-    props_setter: Box<Fn(&mut Box<Lifecycle>, *mut Props)>,
+    state_saver: StateSaver,
     rendered: Option<Box<VNode>>,
-    #[cfg(target_arch = "wasm32")]
+    /// The load being awaited by the async server render
+    #[cfg(not(target_arch = "wasm32"))]
+    loading: Option<Load>,
     state_changed: Rc<RefCell<bool>>,
+    ns: Option<Namespace>,
+    bindings: Vec<Binding>,
+    notifier: Option<Rc<Fn()>>,
+    provided: Vec<Provided>,
 }
 
 impl VComponent {
-    #[cfg(target_arch = "wasm32")]
     pub fn new<T: Component + 'static>(props: T::Props) -> VComponent {
-        let state_changed = Rc::new(RefCell::new(false));
-        let state_changed_writer = state_changed.clone();
         let props: *mut Props = unsafe {
-            mem::transmute(Box::into_raw(Box::new(props)))
+            mem::transmute::<*mut T::Props, *mut Props>(Box::into_raw(Box::new(props)))
         };
         VComponent {
             type_id: TypeId::of::<T>(),
             instance: None,
             props: Some(props),
-            initializer: Box::new(move |props, render_req| {
-                let state_changed = state_changed_writer.clone();
-                let notifier = Box::new(move || {
-                    *state_changed.borrow_mut() = true;
-                    render_req.send();
-                });
+            initializer: Box::new(|props, notifier| {
                 let props: T::Props = unsafe {
-                    *Box::from_raw(mem::transmute(props))
+                    *Box::from_raw(mem::transmute::<*mut Props, *mut T::Props>(props))
                 };
-                Box::new(T::create(props, notifier))
+                Box::new(T::create(props, Box::new(move || notifier())))
             }),
             props_setter: Box::new(|instance, props| {
                 let props: T::Props = unsafe {
-                    *Box::from_raw(mem::transmute(props))
+                    *Box::from_raw(mem::transmute::<*mut Props, *mut T::Props>(props))
                 };
                 let instance = instance.as_any().downcast_ref::<T>()
                     .expect("Impossible. The instance cannot be of any other type");
-                let is_diff = instance.eq_props(&props);
-                if is_diff {
+                let is_same = instance.eq_props(&props);
+                if !is_same {
                     T::update(instance, props);
                 }
             }),
+            #[cfg(not(target_arch = "wasm32"))]
+            state_saver: Box::new(|instance| {
+                let instance = instance.downcast_ref::<T>()
+                    .expect("Impossible. The instance cannot be of any other type");
+                instance.save_state()
+            }),
             rendered: None,
-            state_changed,
+            #[cfg(not(target_arch = "wasm32"))]
+            loading: None,
+            state_changed: Rc::new(RefCell::new(false)),
+            ns: None,
+            bindings: vec![],
+            notifier: None,
+            provided: vec![],
         }
     }
 
-    #[cfg(not(target_arch = "wasm32"))]
-    pub fn new<T: Component + 'static>(props: T::Props) -> VComponent {
-        let state_changed = Rc::new(RefCell::new(false));
-        let state_changed_writer = state_changed.clone();
-        let props: *mut Props = unsafe {
-            mem::transmute(Box::into_raw(Box::new(props)))
-        };
-        VComponent {
-            type_id: TypeId::of::<T>(),
-            instance: None,
-            props: Some(props),
-            initializer: Box::new(move |props| {
-                let state_changed = state_changed_writer.clone();
-                let notifier = Box::new(move || {
-                    *state_changed.borrow_mut() = true;
-                });
-                let props: T::Props = unsafe {
-                    *Box::from_raw(mem::transmute(props))
-                };
-                Box::new(T::create(props, notifier))
-            }),
-            rendered: None
+    /// Adds a binding of a listener of the parent onto an event emitted by the component `T`.
+    /// The `binding` is called with `true` to bind and with `false` to unbind.
+    pub fn add_binding<T, F>(&mut self, binding: F) where
+        T: Component + 'static,
+        F: Fn(&T, bool) + 'static {
+        self.bindings.push(Box::new(move |instance, bind| {
+            let instance = instance.downcast_ref::<T>()
+                .expect("Impossible. The instance cannot be of any other type");
+            binding(instance, bind);
+        }));
+    }
+
+    fn apply_bindings(&self, bind: bool) {
+        let instance = self.instance.as_ref().expect("The component is not yet initialized");
+        for binding in self.bindings.iter() {
+            binding(instance.as_any(), bind);
         }
     }
 
-    #[cfg(target_arch = "wasm32")]
-    fn init(&mut self, render_req: RenderRequestSender) {
-        let initializer = &self.initializer;
-        let props = self.props.take().expect("Impossible. The props are always provided");
-        let instance = initializer(props, render_req);
-        instance.created();
-        self.instance = Some(instance);
+    /// Replaces the bindings of the initialized component with those of the `newer` one.
+    fn rebind(&mut self, newer: &mut VComponent) {
+        self.apply_bindings(false);
+        self.bindings = mem::take(&mut newer.bindings);
+        self.apply_bindings(true);
     }
 
-    #[cfg(not(target_arch = "wasm32"))]
-    fn init(&mut self) {
-        let initializer = &self.initializer;
+    fn init(&mut self, render_req: Option<RenderRequestSender>) {
+        let state_changed = self.state_changed.clone();
+        let notifier: Rc<Fn()> = Rc::new(move || {
+            *state_changed.borrow_mut() = true;
+            // Server rendered components are never re-rendered
+            if let Some(ref render_req) = render_req {
+                render_req.send();
+            }
+        });
         let props = self.props.take().expect("Impossible. The props are always provided");
-        let instance = initializer(props);
-        instance.created();
+        let (instance, provided) = {
+            let initializer = &self.initializer;
+            let initializer_notifier = notifier.clone();
+            context::as_current(notifier.clone(), move || initializer(props, initializer_notifier))
+        };
         self.instance = Some(instance);
+        self.notifier = Some(notifier);
+        self.provided = provided;
+        self.apply_bindings(true);
+        self.instance.as_ref().unwrap().created();
     }
 
     // Only use this when the Type of the props is same as that of this Component's props
-    #[cfg(target_arch = "wasm32")]
     unsafe fn set_props(&mut self, props: *mut Props) {
         debug_assert!(self.instance.is_some());
         let props_setter = &self.props_setter;
         props_setter(self.instance.as_mut().unwrap(), props);
     }
 
-    #[cfg(target_arch = "wasm32")]
+    /// Renders the instance within the namespace inherited by the component.
+    fn render_instance(&self) -> VNode {
+        let instance = self.instance.as_ref().unwrap();
+        let notifier = self.notifier.clone().unwrap();
+        let (mut rendered, provided) = context::as_current(notifier, || instance.render());
+        debug_assert!(provided.is_empty(), "A context can only be provided while a component is created");
+        if let Some(ns) = self.ns {
+            rendered.inherit_namespace(ns);
+        }
+        rendered
+    }
+
     fn take_props(&mut self) -> *mut Props {
         self.props.take()
             .expect("Props already taken")
     }
 
-    #[cfg(target_arch = "wasm32")]
     fn state_changed(&self) -> bool {
         *self.state_changed.borrow()
     }
 
-    #[cfg(target_arch = "wasm32")]
     fn unset_state_changed(&self) {
         *self.state_changed.borrow_mut() = false;
     }
@@ -166,118 +204,211 @@ impl Debug for VComponent {
     }
 }
 
+impl InheritNamespace for VComponent {
+    /// Also applied to whatever the component renders later on.
+    fn inherit_namespace(&mut self, ns: Namespace) {
+        self.ns = Some(ns);
+        if let Some(ref mut rendered) = self.rendered {
+            rendered.inherit_namespace(ns);
+        }
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 impl ServerRender for VComponent {
     fn server_render(&mut self) {
         debug_assert!(self.instance.is_none());
         debug_assert!(self.rendered.is_none());
-        self.init();
-        let instance = self.instance.as_mut().unwrap();
-        let mut rendered = instance.render();
-        rendered.server_render();
+        self.init(None);
+        let mut rendered = self.render_instance();
+        context::with_provided(&self.provided, || rendered.server_render());
         self.rendered = Some(Box::new(rendered));
     }
 }
 
-#[cfg(target_arch = "wasm32")]
-mod wasm {
+#[cfg(not(target_arch = "wasm32"))]
+impl ServerLoad for VComponent {
+    fn poll_load(&mut self, cx: &mut TaskContext) -> Poll<()> {
+        if self.instance.is_none() {
+            self.init(None);
+            self.loading = self.instance.as_ref().unwrap().load();
+        }
+        if let Some(ref mut loading) = self.loading {
+            if loading.as_mut().poll(cx).is_pending() {
+                return Poll::Pending;
+            }
+        }
+        self.loading = None;
+        if self.rendered.is_none() {
+            // Rendered as soon as it is loaded, after which its children start loading
+            self.rendered = Some(Box::new(self.render_instance()));
+        }
+        let rendered = self.rendered.as_mut().unwrap();
+        context::with_provided(&self.provided, || rendered.poll_load(cx))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ServerStream for VComponent {
+    fn server_stream(mut self, writer: &mut Write) -> io::Result<()> {
+        debug_assert!(self.instance.is_none());
+        self.init(None);
+        let rendered = self.render_instance();
+        if let Some(ref instance) = self.instance {
+            state::save_streamed(|| (self.state_saver)(instance.as_any()));
+        }
+        context::with_provided(&self.provided, || state::streaming_child(0, || rendered.server_stream(writer)))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl CollectStates for VComponent {
+    fn collect_states(&self, path: &mut Vec<usize>, states: &mut SavedStates) {
+        if let Some(ref instance) = self.instance {
+            if let Some(state) = (self.state_saver)(instance.as_any()) {
+                states.insert(state::position_key(path), state);
+            }
+        }
+        if let Some(ref rendered) = self.rendered {
+            path.push(0);
+            rendered.collect_states(path, states);
+            path.pop();
+        }
+    }
+}
+
+mod dom {
     use vdiff::DOMPatch;
     use vcomponent::VComponent;
-    use stdweb::web::Element;
-    use stdweb::web::Node;
     use vdiff::DOMRemove;
     use vdiff::DOMReorder;
     use vdiff::DOMNode;
     use traits::DOMRender;
+    use backend::Backend;
     use events::RenderRequestSender;
+    use context;
+    use hydrate::{DOMHydrate, Hydration};
+    use state;
 
     impl DOMPatch<VComponent> for VComponent {
-        fn patch(mut self, parent: &Element, next: Option<&Node>, old_vnode: Option<VComponent>, render_req: RenderRequestSender) -> Self {
+        fn patch<B: Backend>(mut self, backend: &B, parent: &B::Node, next: Option<&B::Node>, old_vnode: Option<VComponent>, render_req: RenderRequestSender) -> Self {
             // Those that are new here, are unrendered and those old require re-rendering
             if let Some(mut old_comp) = old_vnode {
                 if self.type_id == old_comp.type_id {
                     // Throw out the newer component, reuse older and pass the newer props
+                    old_comp.rebind(&mut self);
                     unsafe {
                         // Safe to use because both the props are of same type as both
                         // components are of same type
                         old_comp.set_props(self.take_props());
                     }
-                    old_comp.dom_render(parent, next, render_req);
+                    old_comp.dom_render(backend, parent, next, render_req);
                     old_comp
                 } else {
-                    old_comp.remove(parent);
-                    create_new_component_render(&mut self, parent, next, render_req);
+                    old_comp.remove(backend, parent);
+                    create_new_component_render(&mut self, backend, parent, next, render_req);
                     self
                 }
             } else {
-                create_new_component_render(&mut self, parent, next, render_req);
+                create_new_component_render(&mut self, backend, parent, next, render_req);
                 self
             }
         }
     }
 
-    fn create_new_component_render(vcomp: &mut VComponent, parent: &Element, next: Option<&Node>, render_req: RenderRequestSender) {
+    fn create_new_component_render<B: Backend>(vcomp: &mut VComponent, backend: &B, parent: &B::Node, next: Option<&B::Node>, render_req: RenderRequestSender) {
         debug_assert!(vcomp.instance.is_none());
         debug_assert!(vcomp.rendered.is_none());
         // Requires an initial render as they are very new
-        vcomp.dom_render(parent, next, render_req);
+        vcomp.dom_render(backend, parent, next, render_req);
+    }
+
+    impl DOMHydrate for VComponent {
+        fn hydrate<B: Backend>(mut self, backend: &B, hydration: &mut Hydration<B>, render_req: RenderRequestSender) -> Self {
+            debug_assert!(self.instance.is_none());
+            debug_assert!(self.rendered.is_none());
+            // Created with the state saved by the server at the same position
+            let saved_state = hydration.saved_state();
+            state::restoring(saved_state, || self.init(Some(render_req.clone())));
+            self.instance.as_ref().unwrap().before_mount();
+            let rendered = self.render_instance();
+            hydration.enter(0);
+            let rendered = context::with_provided(&self.provided, || {
+                rendered.hydrate(backend, hydration, render_req)
+            });
+            hydration.leave();
+            self.rendered = Some(Box::new(rendered));
+            self.instance.as_ref().unwrap().mounted();
+            self
+        }
     }
 
     impl DOMRemove for VComponent {
-        fn remove(mut self, parent: &Element) {
+        fn remove<B: Backend>(mut self, backend: &B, parent: &B::Node) {
             debug_assert!(self.instance.is_some());
             debug_assert!(self.rendered.is_some());
-            self.rendered.unwrap().remove(parent);
+            self.instance.as_ref().unwrap().before_destroy();
+            self.rendered.unwrap().remove(backend, parent);
             self.instance.as_mut().unwrap().destroyed();
         }
     }
 
     impl DOMReorder for VComponent {
-        fn move_to_last(&self, parent: &Element) {
+        fn move_to_last<B: Backend>(&self, backend: &B, parent: &B::Node) {
             if let Some(ref rendered) = self.rendered {
-                rendered.move_to_last(parent);
+                rendered.move_to_last(backend, parent);
             }
         }
 
-        fn move_before(&self, parent: &Element, next: &Node) {
+        fn move_before<B: Backend>(&self, backend: &B, parent: &B::Node, next: &B::Node) {
             if let Some(ref rendered) = self.rendered {
-                rendered.move_before(parent, next);
+                rendered.move_before(backend, parent, next);
             }
         }
     }
 
     impl DOMNode for VComponent {
-        fn dom_node(&self) -> Option<Node> {
-            self.rendered.as_ref().and_then(|it| it.dom_node())
+        fn dom_node<B: Backend>(&self) -> Option<B::Node> {
+            self.rendered.as_ref().and_then(|it| it.dom_node::<B>())
         }
     }
 
     impl DOMRender for VComponent {
-        fn dom_render(&mut self, parent: &Element, next: Option<&Node>, render_req: RenderRequestSender) {
+        fn dom_render<B: Backend>(&mut self, backend: &B, parent: &B::Node, next: Option<&B::Node>, render_req: RenderRequestSender) {
             if self.instance.is_none() {
-                self.init(render_req.clone());
+                self.init(Some(render_req.clone()));
             }
             if self.rendered.is_none() {
                 // First time being rendered
-                let instance = self.instance.as_mut().unwrap();
-                let rendered = instance.render();
-                let rendered = rendered.patch(parent, next, None, render_req);
+                self.instance.as_ref().unwrap().before_mount();
+                let rendered = self.render_instance();
+                let rendered = context::with_provided(&self.provided, || {
+                    rendered.patch(backend, parent, next, None, render_req)
+                });
                 self.rendered = Some(Box::new(rendered));
-                instance.mounted();
+                self.instance.as_ref().unwrap().mounted();
             } else {
-                if self.state_changed() {
+                let should_render = self.state_changed() && {
                     self.unset_state_changed();
+                    self.instance.as_ref().unwrap().should_render()
+                };
+                if should_render {
+                    self.instance.as_ref().unwrap().before_update();
                     let old_rendered = self.rendered.take().unwrap();
-                    let instance = self.instance.as_mut().unwrap();
-                    let newly_rendered = instance.render();
-                    let newly_rendered = newly_rendered.patch(parent, next, Some(*old_rendered), render_req);
+                    let newly_rendered = self.render_instance();
+                    let newly_rendered = context::with_provided(&self.provided, || {
+                        newly_rendered.patch(backend, parent, next, Some(*old_rendered), render_req)
+                    });
                     self.rendered = Some(Box::new(newly_rendered));
-                    instance.updated();
+                    self.instance.as_ref().unwrap().updated();
                 } else {
-                    // No change. Propagate till a changed/new component is found
-                    self.rendered.as_mut().unwrap().dom_render(parent, next, render_req);
+                    // No change or skipped. Propagate till a changed/new component is found
+                    let rendered = self.rendered.as_mut().unwrap();
+                    context::with_provided(&self.provided, || {
+                        rendered.dom_render(backend, parent, next, render_req)
+                    });
                 }
             }
         }
     }
-}
\ No newline at end of file
+}
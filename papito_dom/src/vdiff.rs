@@ -1,38 +1,71 @@
-use stdweb::web::Element;
-use stdweb::web::Node;
+use std::any::Any;
+use std::fmt::Debug;
+use std::fmt::{Formatter, self};
+use std::rc::Rc;
+use backend::Backend;
 use events::RenderRequestSender;
 
 /// Required to update the DOM on the `parent` node. It is also tasked with Diffing along
 /// as it creates patches.
 pub trait DOMPatch<T> {
-    fn patch(self, parent: &Element, next: Option<&Node>, old_vnode: Option<T>, render_req: RenderRequestSender) -> Self;
+    fn patch<B: Backend>(self, backend: &B, parent: &B::Node, next: Option<&B::Node>, old_vnode: Option<T>, render_req: RenderRequestSender) -> Self;
 }
 
 /// Required when removing stale `VNodes`.
 pub trait DOMRemove {
-    fn remove(self, parent: &Element);
+    fn remove<B: Backend>(self, backend: &B, parent: &B::Node);
 }
 
 /// Required when re-ordering the `VList` children. Reordering is done by appending the dom node
 /// again in a new order.
 pub trait DOMReorder {
-    fn move_to_last(&self, parent: &Element);
+    fn move_to_last<B: Backend>(&self, backend: &B, parent: &B::Node);
 
-    fn move_before(&self, parent: &Element, next: &Node);
+    fn move_before<B: Backend>(&self, backend: &B, parent: &B::Node, next: &B::Node);
 }
 
 pub trait DOMNode {
-    fn dom_node(&self) -> Option<Node>;
+    fn dom_node<B: Backend>(&self) -> Option<B::Node>;
 }
 
+/// A reference to the node created by a `Backend` for a `VNode`. It is type erased so that the
+/// `VNode` tree need not be generic over the backend.
+#[derive(Clone)]
+pub struct DOMRef(Rc<Any>);
+
+impl DOMRef {
+    pub fn new<B: Backend>(node: B::Node) -> DOMRef {
+        DOMRef(Rc::new(node))
+    }
+
+    pub fn node<B: Backend>(&self) -> &B::Node {
+        self.0.downcast_ref()
+            .expect("The node was created by a different backend")
+    }
+}
+
+impl Debug for DOMRef {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "DOMRef")
+    }
+}
+
+impl PartialEq for DOMRef {
+    fn eq(&self, other: &DOMRef) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for DOMRef {}
+
 impl<T, Q> DOMPatch<T> for Option<Q> where
     Q: DOMPatch<T>,
     T: DOMRemove {
-    fn patch(self, parent: &Element, next: Option<&Node>, old_vnode: Option<T>, render_req: RenderRequestSender) -> Self {
+    fn patch<B: Backend>(self, backend: &B, parent: &B::Node, next: Option<&B::Node>, old_vnode: Option<T>, render_req: RenderRequestSender) -> Self {
         if let Some(this) = self {
-            Some(this.patch(parent, next, old_vnode, render_req))
+            Some(this.patch(backend, parent, next, old_vnode, render_req))
         } else {
-            old_vnode.remove(parent);
+            old_vnode.remove(backend, parent);
             None
         }
     }
@@ -40,16 +73,16 @@ impl<T, Q> DOMPatch<T> for Option<Q> where
 
 impl<T, Q> DOMPatch<Q> for Box<T> where
     T: DOMPatch<Q> {
-    fn patch(self, parent: &Element, next: Option<&Node>, old_vnode: Option<Q>, render_req: RenderRequestSender) -> Self {
+    fn patch<B: Backend>(self, backend: &B, parent: &B::Node, next: Option<&B::Node>, old_vnode: Option<Q>, render_req: RenderRequestSender) -> Self {
         let this = *self;
-        Box::new(this.patch(parent, next, old_vnode, render_req))
+        Box::new(this.patch(backend, parent, next, old_vnode, render_req))
     }
 }
 
 impl<T: DOMRemove> DOMRemove for Option<T> {
-    fn remove(self, parent: &Element) {
+    fn remove<B: Backend>(self, backend: &B, parent: &B::Node) {
         if let Some(inner) = self {
-            inner.remove(parent);
+            inner.remove(backend, parent);
         }
     }
-}
\ No newline at end of file
+}
@@ -1,110 +1,444 @@
 use CowStr;
-use indexmap::IndexMap;
+use indexmap::{IndexMap, IndexSet};
 use std::fmt::{self, Formatter};
 use std::fmt::Display;
-#[cfg(target_arch = "wasm32")]
-use stdweb::web::Element;
-#[cfg(target_arch = "wasm32")]
 use events::DOMEvent;
+use vdiff::DOMRef;
+use escape::Escaped;
 use vnode::VNode;
+use namespace::{Namespace, InheritNamespace};
+use validate::{self, ValidationError};
 #[cfg(not(target_arch = "wasm32"))]
-use traits::ServerRender;
+use traits::{ServerRender, ServerStream, CollectStates, ServerLoad};
+#[cfg(not(target_arch = "wasm32"))]
+use std::task::{Context as TaskContext, Poll};
+#[cfg(not(target_arch = "wasm32"))]
+use state::{self, SavedStates};
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::{self, Write};
 
-#[derive(Debug, Eq, PartialEq)]
-pub struct ClassString(CowStr);
+/// The set of classes of an element. Kept in the order they were added.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct ClassList(IndexSet<CowStr>);
+
+impl ClassList {
+    pub fn new() -> ClassList {
+        ClassList::default()
+    }
+
+    pub fn add<T: Into<CowStr>>(&mut self, class: T) {
+        let class = class.into();
+        if !class.is_empty() {
+            self.0.insert(class);
+        }
+    }
+
+    pub fn remove(&mut self, class: &str) {
+        self.0.retain(|it| it != class);
+    }
+
+    pub fn contains(&self, class: &str) -> bool {
+        self.0.contains(class)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
 
-impl Display for ClassString {
+impl Display for ClassList {
+    /// Writes the space separated class names.
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        write!(f, " class=\"{}\"", self.0)
+        for (i, class) in self.0.iter().enumerate() {
+            if i != 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", class)?;
+        }
+        Ok(())
+    }
+}
+
+/// The inline style declarations of an element. Kept in the order they were added.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Style(IndexMap<CowStr, CowStr>);
+
+impl Style {
+    pub fn new() -> Style {
+        Style::default()
+    }
+
+    pub fn set<K: Into<CowStr>, V: Into<CowStr>>(&mut self, property: K, value: V) {
+        self.0.insert(property.into(), value.into());
+    }
+
+    pub fn remove(&mut self, property: &str) {
+        self.0.retain(|k, _| k != property);
+    }
+
+    pub fn get(&self, property: &str) -> Option<&str> {
+        self.0.get(property).map(|it| &**it)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Display for Style {
+    /// Writes the declarations as `property: value;` separated by a space.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for (i, (k, v)) in self.0.iter().enumerate() {
+            if i != 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}: {};", k, v)?;
+        }
+        Ok(())
+    }
+}
+
+/// The value of an attribute or a property. A `Bool` attribute is present only when it is
+/// `true`, as is the html semantics for boolean attributes such as `disabled`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum AttrValue {
+    Str(CowStr),
+    Bool(bool),
+    Absent,
+}
+
+impl Display for AttrValue {
+    /// Writes the value as an attribute value along with the preceding `=`, if any.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            AttrValue::Str(ref value) => write!(f, "=\"{}\"", Escaped(value)),
+            _ => Ok(())
+        }
+    }
+}
+
+impl AttrValue {
+    /// Whether the attribute is rendered at all.
+    pub fn is_present(&self) -> bool {
+        match *self {
+            AttrValue::Str(_) | AttrValue::Bool(true) => true,
+            AttrValue::Bool(false) | AttrValue::Absent => false
+        }
     }
 }
 
 #[derive(Debug, Eq, PartialEq)]
-pub struct Attributes(IndexMap<CowStr, CowStr>);
+pub struct Attributes(IndexMap<CowStr, AttrValue>);
 
 impl Display for Attributes {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        for (k, v) in self.0.iter() {
-            write!(f, " {}=\"{}\"", k, v)?;
+        for (k, v) in self.0.iter().filter(|&(_, v)| v.is_present()) {
+            write!(f, " {}{}", k, v)?;
         }
         Ok(())
     }
 }
 
-#[cfg(target_arch = "wasm32")]
+/// The attributes that are applied on the DOM as live properties instead, so that they do not go
+/// stale after the user edits the form controls.
+const PROPERTIES: &[&str] = &["value", "checked", "selected"];
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct Properties(IndexMap<CowStr, AttrValue>);
+
+impl Display for Properties {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        // Server rendered as attributes which are the initial values of the properties
+        write!(f, "{}", Attributes(self.0.clone()))
+    }
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub struct Events(Vec<Box<DOMEvent>>);
 
 #[derive(Debug, Eq, PartialEq)]
 pub struct VElement {
     tag: CowStr,
-    class: Option<ClassString>,
+    class: Option<ClassList>,
+    style: Option<Style>,
     attrs: Option<Attributes>,
-    child: Option<Box<VNode>>,
-    is_self_closing: bool,
-    #[cfg(target_arch = "wasm32")]
+    props: Option<Properties>,
+    children: Vec<VNode>,
     events: Events,
-    #[cfg(target_arch = "wasm32")]
-    dom_ref: Option<Element>,
+    /// The namespace declared by the element itself.
+    ns: Option<Namespace>,
+    /// The namespace passed down by the ancestors.
+    inherited_ns: Option<Namespace>,
+    dom_ref: Option<DOMRef>,
 }
 
 impl VElement {
-    pub fn new(tag: CowStr, class: Option<ClassString>, attrs: Option<Attributes>, child: Option<VNode>, is_self_closing: bool) -> VElement {
-        VElement {
-            // TODO: validate tag string first
+    /// Creates an element. The `style` attribute is separated out of the `attrs` as a `Style`
+    /// and the `value`, `checked` and `selected` attributes to be applied as properties.
+    ///
+    /// An `svg` or `math` element starts its namespace which is then inherited by its descendants.
+    /// Any element may declare its own namespace with a known `xmlns` attribute.
+    ///
+    /// Panics if the element is invalid. Use `try_new` to handle it instead.
+    pub fn new(tag: CowStr, class: Option<ClassList>, attrs: Option<Attributes>, children: Vec<VNode>) -> VElement {
+        VElement::try_new(tag, class, attrs, children)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Creates an element after validating its tag and attribute names.
+    pub fn try_new(tag: CowStr, class: Option<ClassList>, attrs: Option<Attributes>, children: Vec<VNode>) -> Result<VElement, ValidationError> {
+        validate::validate_tag_name(&tag)?;
+        if let Some(ref attrs) = attrs {
+            for (k, _) in attrs.0.iter() {
+                validate::validate_attribute_name(k)?;
+            }
+        }
+        let (ns, attrs) = split_into_namespace_and_attrs(attrs);
+        let ns = ns.or_else(|| Namespace::of_tag(&tag));
+        if ns.unwrap_or(Namespace::Html) == Namespace::Html && is_void_element(&tag) && !children.is_empty() {
+            return Err(ValidationError::VoidElementWithChildren(tag.to_string()));
+        }
+        if ns.unwrap_or(Namespace::Html) == Namespace::Html && is_raw_text_element(&tag) {
+            // The text is written as is, so it cannot end the element before its closing tag
+            let end_tag = format!("</{}", tag.to_ascii_lowercase());
+            let has_end_tag = children.iter().any(|child| match *child {
+                VNode::Text(ref text) => text.content().to_ascii_lowercase().contains(&end_tag),
+                _ => false
+            });
+            if has_end_tag {
+                return Err(ValidationError::EndTagWithinRawText(tag.to_string()));
+            }
+        }
+        let (style, attrs) = split_into_style_and_attrs(attrs);
+        let (attrs, props) = split_into_attrs_and_props(attrs);
+        let mut vel = VElement {
             tag,
-            class,
+            class: class.and_then(|it| if it.is_empty() { None } else { Some(it) }),
+            style,
             attrs,
-            child: child.map(|it| Box::new(it)),
-            is_self_closing,
-            #[cfg(target_arch = "wasm32")]
+            props,
+            children,
             events: Events(vec![]),
-            #[cfg(target_arch = "wasm32")]
+            ns,
+            inherited_ns: None,
             dom_ref: None,
+        };
+        if vel.ns.is_some() {
+            vel.pass_namespace_to_children();
+        }
+        Ok(vel)
+    }
+
+    /// Overrides the namespace of the element, along with the descendants that inherit it.
+    pub fn set_namespace(&mut self, ns: Namespace) {
+        self.ns = Some(ns);
+        self.pass_namespace_to_children();
+    }
+
+    /// The namespace the element is created within. Defaults to html.
+    pub fn namespace(&self) -> Namespace {
+        self.ns.or(self.inherited_ns).unwrap_or(Namespace::Html)
+    }
+
+    fn pass_namespace_to_children(&mut self) {
+        // The content of a `foreignObject` is back to being html
+        let ns = if self.tag == "foreignObject" { Namespace::Html } else { self.namespace() };
+        for child in self.children.iter_mut() {
+            child.inherit_namespace(ns);
         }
     }
 
-    #[cfg(target_arch = "wasm32")]
-    pub fn dom_ref(&self) -> Option<&Element> {
+    /// Warns about an unknown html tag in the strict mode. Checked only once rendered, as the
+    /// namespace of the element is not known before.
+    fn warn_if_unknown(&self) {
+        if validate::is_strict_mode() && self.namespace() == Namespace::Html && !validate::is_known_html_tag(&self.tag) {
+            validate::warn_unknown_tag(&self.tag);
+        }
+    }
+
+    /// Whether the element is one of the html void elements which can neither have children
+    /// nor a closing tag.
+    pub fn is_void(&self) -> bool {
+        self.namespace() == Namespace::Html && is_void_element(&self.tag)
+    }
+
+    /// Whether the element is a `script` or a `style`, whose text is not escaped.
+    pub fn has_raw_text(&self) -> bool {
+        self.namespace() == Namespace::Html && is_raw_text_element(&self.tag)
+    }
+
+    pub fn dom_ref(&self) -> Option<&DOMRef> {
         self.dom_ref.as_ref()
     }
 
-    #[cfg(target_arch = "wasm32")]
     pub fn set_events(&mut self, events: Vec<Box<DOMEvent>>) {
         self.events.0 = events;
     }
 }
 
-impl Display for VElement {
+impl InheritNamespace for VElement {
+    fn inherit_namespace(&mut self, ns: Namespace) {
+        if self.inherited_ns == Some(ns) {
+            return;
+        }
+        self.inherited_ns = Some(ns);
+        self.pass_namespace_to_children();
+    }
+}
+
+/// The opening tag of an element along with its attributes.
+struct OpenTag<'a>(&'a VElement);
+
+impl<'a> Display for OpenTag<'a> {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        write!(f, "<{}", self.tag)?;
-        if let Some(ref class) = self.class {
-            write!(f, "{}", class)?;
+        let el = self.0;
+        write!(f, "<{}", el.tag)?;
+        // Only declared where the namespace differs from the one it is within
+        if let Some(ns) = el.ns {
+            if ns != el.inherited_ns.unwrap_or(Namespace::Html) {
+                write!(f, " xmlns=\"{}\"", ns.uri())?;
+            }
+        }
+        if let Some(ref class) = el.class {
+            write!(f, " class=\"{}\"", Escaped(&class.to_string()))?;
         }
-        if let Some(ref attrs) = self.attrs {
+        if let Some(ref style) = el.style {
+            write!(f, " style=\"{}\"", Escaped(&style.to_string()))?;
+        }
+        if let Some(ref attrs) = el.attrs {
             write!(f, "{}", attrs)?;
         }
-        if self.is_self_closing {
-            write!(f, ">")
-        } else {
-            write!(f, ">")?;
-            if let Some(ref child) = self.child {
-                write!(f, "{}", child)?;
+        if let Some(ref props) = el.props {
+            write!(f, "{}", props)?;
+        }
+        write!(f, ">")
+    }
+}
+
+impl Display for VElement {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", OpenTag(self))?;
+        if self.is_void() {
+            return Ok(());
+        }
+        for child in self.children.iter() {
+            match *child {
+                VNode::Text(ref text) if self.has_raw_text() => f.write_str(text.content())?,
+                ref child => write!(f, "{}", child)?
             }
-            write!(f, "</{}>", self.tag)
         }
+        write!(f, "</{}>", self.tag)
     }
 }
 
-impl<A: Into<CowStr>> From<A> for ClassString {
+impl<A: Into<CowStr>> From<A> for ClassList {
+    /// Parses the whitespace separated class names.
     fn from(item: A) -> Self {
-        ClassString(item.into())
+        let mut class_list = ClassList::new();
+        match item.into() {
+            CowStr::Borrowed(classes) => {
+                for class in classes.split_whitespace() {
+                    class_list.add(class);
+                }
+            }
+            CowStr::Owned(classes) => {
+                for class in classes.split_whitespace() {
+                    class_list.add(class.to_string());
+                }
+            }
+        }
+        class_list
+    }
+}
+
+impl<A: Into<CowStr>> From<A> for Style {
+    /// Parses the `;` separated `property: value` declarations.
+    fn from(item: A) -> Self {
+        let mut style = Style::new();
+        for declaration in split_declarations(&item.into()) {
+            let mut parts = declaration.splitn(2, ':');
+            let property = parts.next().unwrap().trim();
+            let value = parts.next().map(|it| it.trim()).unwrap_or("");
+            if !property.is_empty() && !value.is_empty() {
+                style.set(property.to_string(), value.to_string());
+            }
+        }
+        style
+    }
+}
+
+/// Splits the declarations on the `;` which are neither within the parentheses nor the quotes, as
+/// in `background: url(data:image/png;base64,...)`.
+fn split_declarations(style: &str) -> Vec<&str> {
+    let mut declarations = vec![];
+    let mut depth = 0;
+    let mut quote = None;
+    let mut is_escaped = false;
+    let mut start = 0;
+    for (i, ch) in style.char_indices() {
+        if is_escaped {
+            is_escaped = false;
+            continue;
+        }
+        match (ch, quote) {
+            ('\\', _) => is_escaped = true,
+            (_, Some(q)) if ch == q => quote = None,
+            (_, Some(_)) => {}
+            ('"', None) | ('\'', None) => quote = Some(ch),
+            ('(', None) => depth += 1,
+            (')', None) if depth > 0 => depth -= 1,
+            (';', None) if depth == 0 => {
+                declarations.push(&style[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    declarations.push(&style[start..]);
+    declarations
+}
+
+macro_rules! impl_conversion_to_attr_value {
+    ($( $from:ty ),*) => {
+        $(
+            impl From<$from> for AttrValue {
+                fn from(item: $from) -> Self {
+                    AttrValue::Str(item.into())
+                }
+            }
+        )*
+    };
+}
+
+impl_conversion_to_attr_value!(&'static str, String, CowStr);
+
+impl From<ClassList> for AttrValue {
+    fn from(item: ClassList) -> Self {
+        AttrValue::Str(item.to_string().into())
+    }
+}
+
+impl From<Style> for AttrValue {
+    fn from(item: Style) -> Self {
+        AttrValue::Str(item.to_string().into())
+    }
+}
+
+impl From<bool> for AttrValue {
+    fn from(item: bool) -> Self {
+        AttrValue::Bool(item)
+    }
+}
+
+impl<T: Into<AttrValue>> From<Option<T>> for AttrValue {
+    fn from(item: Option<T>) -> Self {
+        item.map(|it| it.into()).unwrap_or(AttrValue::Absent)
     }
 }
 
 impl<A, B> From<Vec<(A, B)>> for Attributes where
     A: Into<CowStr>,
-    B: Into<CowStr> {
+    B: Into<AttrValue> {
     fn from(item: Vec<(A, B)>) -> Self {
         Attributes(item.into_iter()
             .map(|(k, v)| (k.into(), v.into()))
@@ -112,202 +446,422 @@ impl<A, B> From<Vec<(A, B)>> for Attributes where
     }
 }
 
-impl<A, B, C> From<(A, Vec<(B, C)>, VNode, bool)> for VElement where
-    A: Into<CowStr>,
-    B: Into<CowStr>,
-    C: Into<CowStr> {
-    fn from(item: (A, Vec<(B, C)>, VNode, bool)) -> Self {
-        let tag = item.0.into();
-        let (class, attrs) = split_into_class_and_attrs(item.1.into());
-        VElement::new(tag, class, attrs, Some(item.2), item.3)
+/// Children of an element, that may be passed as a single `VNode`, a `Vec` or a tuple of them.
+pub trait IntoChildren {
+    fn into_children(self) -> Vec<VNode>;
+}
+
+impl IntoChildren for VNode {
+    fn into_children(self) -> Vec<VNode> {
+        vec![self]
     }
 }
 
-impl<A, B, C> From<(A, Vec<(B, C)>, VNode)> for VElement where
-    A: Into<CowStr>,
-    B: Into<CowStr>,
-    C: Into<CowStr> {
-    fn from(item: (A, Vec<(B, C)>, VNode)) -> Self {
-        let tag = item.0.into();
-        let (class, attrs) = split_into_class_and_attrs(item.1.into());
-        VElement::new(tag, class, attrs, Some(item.2), false)
+impl IntoChildren for Vec<VNode> {
+    fn into_children(self) -> Vec<VNode> {
+        self
     }
 }
 
-impl<A, B, C> From<(A, Vec<(B, C)>, bool)> for VElement where
+macro_rules! vnode_ty {
+    ($t:ident) => { VNode };
+}
+
+macro_rules! impl_into_children_for_tuple {
+    ($( ( $( $t:ident ),* ) ),*) => {
+        $(
+            impl IntoChildren for ( $( vnode_ty!($t) ),* ) {
+                fn into_children(self) -> Vec<VNode> {
+                    let ( $( $t ),* ) = self;
+                    vec![ $( $t ),* ]
+                }
+            }
+        )*
+    };
+}
+
+impl_into_children_for_tuple!(
+    (a, b),
+    (a, b, c),
+    (a, b, c, d),
+    (a, b, c, d, e),
+    (a, b, c, d, e, f),
+    (a, b, c, d, e, f, g),
+    (a, b, c, d, e, f, g, h),
+    (a, b, c, d, e, f, g, h, i),
+    (a, b, c, d, e, f, g, h, i, j),
+    (a, b, c, d, e, f, g, h, i, j, k),
+    (a, b, c, d, e, f, g, h, i, j, k, l)
+);
+
+// The conversions used by `h!`, which panic on an invalid element just like `VElement::new`.
+impl<A, B, C, D> From<(A, Vec<(B, C)>, D)> for VElement where
     A: Into<CowStr>,
     B: Into<CowStr>,
-    C: Into<CowStr> {
-    fn from(item: (A, Vec<(B, C)>, bool)) -> Self {
+    C: Into<AttrValue>,
+    D: IntoChildren {
+    fn from(item: (A, Vec<(B, C)>, D)) -> Self {
         let tag = item.0.into();
         let (class, attrs) = split_into_class_and_attrs(item.1.into());
-        VElement::new(tag, class, attrs, None, item.2)
+        VElement::new(tag, class, attrs, item.2.into_children())
     }
 }
 
 impl<A, B, C> From<(A, Vec<(B, C)>)> for VElement where
     A: Into<CowStr>,
     B: Into<CowStr>,
-    C: Into<CowStr> {
+    C: Into<AttrValue> {
     fn from(item: (A, Vec<(B, C)>)) -> Self {
         let tag = item.0.into();
         let (class, attrs) = split_into_class_and_attrs(item.1.into());
-        VElement::new(tag, class, attrs, None, false)
+        VElement::new(tag, class, attrs, vec![])
     }
 }
 
-impl<A> From<(A, bool)> for VElement where
+impl<A> From<(A, ())> for VElement where
     A: Into<CowStr> {
-    fn from(item: (A, bool)) -> Self {
+    fn from(item: (A, ())) -> Self {
         let tag = item.0.into();
-        VElement::new(tag, None, None, None, item.1)
+        VElement::new(tag, None, None, vec![])
     }
 }
 
-impl<A> From<(A, ())> for VElement where
-    A: Into<CowStr> {
-    fn from(item: (A, ())) -> Self {
+impl<A, D> From<(A, D)> for VElement where
+    A: Into<CowStr>,
+    D: IntoChildren {
+    fn from(item: (A, D)) -> Self {
         let tag = item.0.into();
-        VElement::new(tag, None, None, None, false)
+        VElement::new(tag, None, None, item.1.into_children())
     }
 }
 
-impl<A> From<(A, VNode, bool)> for VElement where
-    A: Into<CowStr> {
-    fn from(item: (A, VNode, bool)) -> Self {
-        let tag = item.0.into();
-        VElement::new(tag, None, None, Some(item.1), item.2)
+/// The html elements that are always empty.
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "keygen", "link", "meta", "param",
+    "source", "track", "wbr"
+];
+
+pub fn is_void_element(tag: &str) -> bool {
+    VOID_ELEMENTS.iter().any(|it| it.eq_ignore_ascii_case(tag))
+}
+
+/// The html elements whose text is not parsed for the character references.
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style"];
+
+pub fn is_raw_text_element(tag: &str) -> bool {
+    RAW_TEXT_ELEMENTS.iter().any(|it| it.eq_ignore_ascii_case(tag))
+}
+
+fn split_into_class_and_attrs(mut attrs: Attributes) -> (Option<ClassList>, Option<Attributes>) {
+    let class = match attrs.0.swap_remove("class") {
+        Some(AttrValue::Str(class)) => Some(class.into()),
+        _ => None
+    };
+    (class, if attrs.0.is_empty() { None } else { Some(attrs) })
+}
+
+fn split_into_namespace_and_attrs(attrs: Option<Attributes>) -> (Option<Namespace>, Option<Attributes>) {
+    if let Some(mut attrs) = attrs {
+        let ns = match attrs.0.get("xmlns") {
+            Some(AttrValue::Str(uri)) => Namespace::from_uri(uri),
+            _ => None
+        };
+        if ns.is_some() {
+            attrs.0.retain(|k, _| k != "xmlns");
+        }
+        (ns, if attrs.0.is_empty() { None } else { Some(attrs) })
+    } else {
+        (None, None)
     }
 }
 
-impl<A> From<(A, VNode)> for VElement where
-    A: Into<CowStr> {
-    fn from(item: (A, VNode)) -> Self {
-        let tag = item.0.into();
-        VElement::new(tag, None, None, Some(item.1), false)
+fn split_into_style_and_attrs(attrs: Option<Attributes>) -> (Option<Style>, Option<Attributes>) {
+    if let Some(mut attrs) = attrs {
+        let style = match attrs.0.get("style") {
+            Some(AttrValue::Str(style)) => Some(Style::from(style.clone())),
+            _ => None
+        };
+        attrs.0.retain(|k, _| k != "style");
+        (
+            style.and_then(|it| if it.is_empty() { None } else { Some(it) }),
+            if attrs.0.is_empty() { None } else { Some(attrs) }
+        )
+    } else {
+        (None, None)
     }
 }
 
-fn split_into_class_and_attrs(mut attrs: Attributes) -> (Option<ClassString>, Option<Attributes>) {
-    let class = attrs.0.swap_remove("class").map(|it| it.into());
-    (class, if attrs.0.len() == 0 { None } else { Some(attrs) })
+fn split_into_attrs_and_props(attrs: Option<Attributes>) -> (Option<Attributes>, Option<Properties>) {
+    if let Some(attrs) = attrs {
+        let (props, attrs): (IndexMap<_, _>, IndexMap<_, _>) = attrs.0.into_iter()
+            .partition(|(k, _)| PROPERTIES.contains(&&**k));
+        (
+            if attrs.is_empty() { None } else { Some(Attributes(attrs)) },
+            if props.is_empty() { None } else { Some(Properties(props)) }
+        )
+    } else {
+        (None, None)
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
 impl ServerRender for VElement {
     fn server_render(&mut self) {
-        if let Some(ref mut child) = self.child {
+        self.warn_if_unknown();
+        for child in self.children.iter_mut() {
             child.server_render();
         }
     }
 }
 
-#[cfg(target_arch = "wasm32")]
-mod wasm {
-    use stdweb::web::{Element, document, INode, IElement};
-    use vdiff::{DOMPatch, DOMRemove};
-    use super::{VElement, ClassString, Attributes, Events};
+/// The children are polled together, so that their loads are resolved concurrently.
+#[cfg(not(target_arch = "wasm32"))]
+impl ServerLoad for VElement {
+    fn poll_load(&mut self, cx: &mut TaskContext) -> Poll<()> {
+        let mut loaded = Poll::Ready(());
+        for child in self.children.iter_mut() {
+            if child.poll_load(cx).is_pending() {
+                loaded = Poll::Pending;
+            }
+        }
+        loaded
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ServerStream for VElement {
+    fn server_stream(self, writer: &mut Write) -> io::Result<()> {
+        self.warn_if_unknown();
+        write!(writer, "{}", OpenTag(&self))?;
+        if self.is_void() {
+            return Ok(());
+        }
+        let has_raw_text = self.has_raw_text();
+        for (i, child) in self.children.into_iter().enumerate() {
+            state::streaming_child(i, || match child {
+                VNode::Text(ref text) if has_raw_text => writer.write_all(text.content().as_bytes()),
+                child => child.server_stream(writer)
+            })?;
+        }
+        write!(writer, "</{}>", self.tag)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl CollectStates for VElement {
+    fn collect_states(&self, path: &mut Vec<usize>, states: &mut SavedStates) {
+        for (i, child) in self.children.iter().enumerate() {
+            path.push(i);
+            child.collect_states(path, states);
+            path.pop();
+        }
+    }
+}
+
+mod dom {
+    use vdiff::{DOMPatch, DOMRemove, DOMRef};
+    use super::{VElement, ClassList, Style, Attributes, Properties, AttrValue, Events};
+    use namespace::Namespace;
+    use vnode::VNode;
     use vdiff::DOMReorder;
     use vdiff::DOMNode;
-    use stdweb::web::Node;
     use traits::DOMRender;
+    use backend::Backend;
     use events::RenderRequestSender;
+    use hydrate::{DOMHydrate, Hydration};
 
     impl DOMPatch<VElement> for VElement {
-        fn patch(mut self, parent: &Element, next: Option<&Node>, old_vnode: Option<VElement>, render_req: RenderRequestSender) -> Self {
+        fn patch<B: Backend>(mut self, backend: &B, parent: &B::Node, next: Option<&B::Node>, old_vnode: Option<VElement>, render_req: RenderRequestSender) -> Self {
             if let Some(old_vnode) = old_vnode {
-                if old_vnode.tag != self.tag {
-                    old_vnode.remove(parent);
-                    create_new_dom_node(self, parent, next, render_req)
+                if old_vnode.tag != self.tag || old_vnode.namespace() != self.namespace() {
+                    old_vnode.remove(backend, parent);
+                    create_new_dom_node(self, backend, parent, next, render_req)
                 } else {
-                    let el = old_vnode.dom_ref().expect("Older element must have dom_ref")
+                    let dom_ref = old_vnode.dom_ref().expect("Older element must have dom_ref")
                         .clone();
-                    self.class = self.class.patch(&el, None, old_vnode.class, render_req.clone());
-                    self.attrs = self.attrs.patch(&el, None, old_vnode.attrs, render_req.clone());
-                    self.child = self.child.patch(&el, None, old_vnode.child.map(|it| *it), render_req.clone());
-                    self.events = self.events.patch(&el, None, Some(old_vnode.events), render_req);
-                    self.dom_ref = Some(el);
+                    {
+                        let el = dom_ref.node::<B>();
+                        self.class = self.class.patch(backend, el, None, old_vnode.class, render_req.clone());
+                        self.style = self.style.patch(backend, el, None, old_vnode.style, render_req.clone());
+                        self.attrs = self.attrs.patch(backend, el, None, old_vnode.attrs, render_req.clone());
+                        self.props = self.props.patch(backend, el, None, old_vnode.props, render_req.clone());
+                        self.children = patch_children(self.children, backend, el, old_vnode.children, render_req.clone());
+                        self.events = self.events.patch(backend, el, None, Some(old_vnode.events), render_req);
+                    }
+                    self.dom_ref = Some(dom_ref);
                     self
                 }
             } else {
-                create_new_dom_node(self, parent, next, render_req)
+                create_new_dom_node(self, backend, parent, next, render_req)
             }
         }
     }
 
+    impl DOMHydrate for VElement {
+        fn hydrate<B: Backend>(mut self, backend: &B, hydration: &mut Hydration<B>, render_req: RenderRequestSender) -> Self {
+            let el = match hydration.next_element(backend, &self.tag) {
+                Some(el) => el,
+                None => {
+                    hydration.mismatched(backend, format!("<{}>", self.tag));
+                    let parent = hydration.parent().clone();
+                    let next = hydration.peek().cloned();
+                    return create_new_dom_node(self, backend, &parent, next.as_ref(), render_req);
+                }
+            };
+            // The attributes are already rendered by the server, only the live values are set
+            self.props = self.props.patch(backend, &el, None, None, render_req.clone());
+            let mut children_hydration = hydration.children_of(backend, &el);
+            self.children = self.children.into_iter()
+                .enumerate()
+                .map(|(i, child)| {
+                    children_hydration.enter(i);
+                    let child = child.hydrate(backend, &mut children_hydration, render_req.clone());
+                    children_hydration.leave();
+                    child
+                })
+                .collect();
+            children_hydration.finish(backend);
+            self.events = self.events.patch(backend, &el, None, None, render_req);
+            self.dom_ref = Some(DOMRef::new::<B>(el));
+            self
+        }
+    }
+
     impl DOMReorder for VElement {
-        fn move_to_last(&self, parent: &Element) {
+        fn move_to_last<B: Backend>(&self, backend: &B, parent: &B::Node) {
             let dom_ref = self.dom_ref().expect("Cannot append previously non-existent element.");
-            parent.append_child(dom_ref);
+            backend.append_child(parent, dom_ref.node::<B>());
         }
 
-        fn move_before(&self, parent: &Element, next: &Node) {
-            parent.insert_before(self.dom_ref().expect("Cannot insert previously non-existent text node."), next)
-                .unwrap();
+        fn move_before<B: Backend>(&self, backend: &B, parent: &B::Node, next: &B::Node) {
+            let dom_ref = self.dom_ref().expect("Cannot insert previously non-existent element.");
+            backend.insert_before(parent, dom_ref.node::<B>(), next);
         }
     }
 
     impl DOMRemove for VElement {
-        fn remove(mut self, parent: &Element) {
+        fn remove<B: Backend>(mut self, backend: &B, parent: &B::Node) {
             let dom_ref = self.dom_ref.take()
                 .expect("Cannot remove non-existent element.");
+            let el = dom_ref.node::<B>();
             // Dismember the events
-            self.events.remove(&dom_ref);
-            // Remove child and their events
-            if let Some(child) = self.child {
-                child.remove(&dom_ref);
+            self.events.remove(backend, el);
+            // Remove children and their events
+            for child in self.children {
+                child.remove(backend, el);
             }
             // Lastly remove self
-            parent.remove_child(&dom_ref).unwrap();
+            backend.remove_child(parent, el);
         }
     }
 
-    fn create_new_dom_node(mut vel: VElement, parent: &Element, next: Option<&Node>, render_req: RenderRequestSender) -> VElement {
-        let el_node = document().create_element(&vel.tag).unwrap();
-        vel.class = vel.class.patch(&el_node, None, None, render_req.clone());
-        vel.attrs = vel.attrs.patch(&el_node, None, None, render_req.clone());
-        vel.child = vel.child.patch(&el_node, None, None, render_req.clone());
-        vel.events = vel.events.patch(&el_node, None, None, render_req);
-        if let Some(next) = next {
-            parent.insert_before(&el_node, next).unwrap();
-        } else {
-            parent.append_child(&el_node);
-        }
-        vel.dom_ref = Some(el_node);
+    fn create_new_dom_node<B: Backend>(mut vel: VElement, backend: &B, parent: &B::Node, next: Option<&B::Node>, render_req: RenderRequestSender) -> VElement {
+        vel.warn_if_unknown();
+        let el_node = match vel.namespace() {
+            Namespace::Html => backend.create_element(&vel.tag),
+            ns => backend.create_element_ns(ns.uri(), &vel.tag)
+        };
+        vel.class = vel.class.patch(backend, &el_node, None, None, render_req.clone());
+        vel.style = vel.style.patch(backend, &el_node, None, None, render_req.clone());
+        vel.attrs = vel.attrs.patch(backend, &el_node, None, None, render_req.clone());
+        vel.props = vel.props.patch(backend, &el_node, None, None, render_req.clone());
+        vel.children = patch_children(vel.children, backend, &el_node, vec![], render_req.clone());
+        vel.events = vel.events.patch(backend, &el_node, None, None, render_req);
+        backend.insert(parent, &el_node, next);
+        vel.dom_ref = Some(DOMRef::new::<B>(el_node));
         vel
     }
 
-    impl DOMPatch<ClassString> for ClassString {
-        fn patch(self, parent: &Element, _: Option<&Node>, old_value: Option<ClassString>, _: RenderRequestSender) -> Self {
-            if Some(&self) != old_value.as_ref() {
-                parent.set_attribute("class", &self.0)
-                    .unwrap();
+    /// Patches the children positionally against the older ones. Patched from the last so that
+    /// the dom node of the next sibling is always known.
+    fn patch_children<B: Backend>(children: Vec<VNode>, backend: &B, parent: &B::Node, mut old_children: Vec<VNode>, render_req: RenderRequestSender) -> Vec<VNode> {
+        if old_children.len() > children.len() {
+            for stale in old_children.drain(children.len()..) {
+                stale.remove(backend, parent);
+            }
+        }
+        let old_children: Vec<_> = old_children.into_iter()
+            .map(Some)
+            .chain((0..).map(|_| None))
+            .take(children.len())
+            .collect();
+        let mut next_node = None;
+        let mut patched: Vec<_> = children.into_iter()
+            .zip(old_children)
+            .rev()
+            .map(|(child, old_child)| {
+                let child = child.patch(backend, parent, next_node.as_ref(), old_child, render_req.clone());
+                next_node = child.dom_node::<B>().or(next_node.take());
+                child
+            })
+            .collect();
+        patched.reverse();
+        patched
+    }
+
+    impl DOMPatch<ClassList> for ClassList {
+        fn patch<B: Backend>(self, backend: &B, parent: &B::Node, _: Option<&B::Node>, old_value: Option<ClassList>, _: RenderRequestSender) -> Self {
+            let old_value = old_value.unwrap_or_default();
+            for class in old_value.0.difference(&self.0) {
+                backend.remove_class(parent, class);
+            }
+            for class in self.0.difference(&old_value.0) {
+                backend.add_class(parent, class);
+            }
+            self
+        }
+    }
+
+    impl DOMRemove for ClassList {
+        fn remove<B: Backend>(self, backend: &B, parent: &B::Node) {
+            backend.remove_attribute(parent, "class");
+        }
+    }
+
+    impl DOMPatch<Style> for Style {
+        fn patch<B: Backend>(self, backend: &B, parent: &B::Node, _: Option<&B::Node>, old_value: Option<Style>, _: RenderRequestSender) -> Self {
+            let mut old_value = old_value.unwrap_or_default();
+            for (k, v) in self.0.iter() {
+                if Some(v) != old_value.0.swap_remove(k).as_ref() {
+                    backend.set_style(parent, k, v);
+                }
+            }
+            for (k, _) in old_value.0.iter() {
+                backend.remove_style(parent, k);
             }
             self
         }
     }
 
-    impl DOMRemove for ClassString {
-        fn remove(self, parent: &Element) {
-            parent.remove_attribute("class");
+    impl DOMRemove for Style {
+        fn remove<B: Backend>(self, backend: &B, parent: &B::Node) {
+            backend.remove_attribute(parent, "style");
+        }
+    }
+
+    fn set_attribute<B: Backend>(backend: &B, node: &B::Node, name: &str, value: &AttrValue) {
+        match *value {
+            AttrValue::Str(ref value) => backend.set_attribute(node, name, value),
+            AttrValue::Bool(true) => backend.set_attribute(node, name, ""),
+            AttrValue::Bool(false) | AttrValue::Absent => backend.remove_attribute(node, name)
         }
     }
 
     impl DOMPatch<Attributes> for Attributes {
-        fn patch(self, parent: &Element, _: Option<&Node>, old_vnode: Option<Attributes>, _: RenderRequestSender) -> Self {
+        fn patch<B: Backend>(self, backend: &B, parent: &B::Node, _: Option<&B::Node>, old_vnode: Option<Attributes>, _: RenderRequestSender) -> Self {
             if let Some(mut old_attributes) = old_vnode {
-                for (k, v) in self.0.clone() {
-                    let old_attr_val = old_attributes.0.swap_remove(&k);
-                    if Some(&v) != old_attr_val.as_ref() {
-                        parent.set_attribute(&k, &v).unwrap();
+                for (k, v) in self.0.iter() {
+                    let old_attr_val = old_attributes.0.swap_remove(k);
+                    if Some(v) != old_attr_val.as_ref() {
+                        set_attribute(backend, parent, k, v);
                     }
                 }
                 for (k, _) in old_attributes.0.iter() {
-                    parent.remove_attribute(&k);
+                    backend.remove_attribute(parent, k);
                 }
             } else {
-                for (k, v) in self.0.clone() {
-                    parent.set_attribute(&k, &v).unwrap();
+                for (k, v) in self.0.iter().filter(|&(_, v)| v.is_present()) {
+                    set_attribute(backend, parent, k, v);
                 }
             }
             self
@@ -315,42 +869,69 @@ mod wasm {
     }
 
     impl DOMRemove for Attributes {
-        fn remove(self, parent: &Element) {
+        fn remove<B: Backend>(self, backend: &B, parent: &B::Node) {
+            for (k, _) in self.0.iter() {
+                backend.remove_attribute(parent, k);
+            }
+        }
+    }
+
+    impl DOMPatch<Properties> for Properties {
+        fn patch<B: Backend>(self, backend: &B, parent: &B::Node, _: Option<&B::Node>, old_vnode: Option<Properties>, _: RenderRequestSender) -> Self {
+            // Always set, because the live values may have been changed by the user since
+            for (k, v) in self.0.iter() {
+                backend.set_property(parent, k, v);
+            }
+            if let Some(old_properties) = old_vnode {
+                for (k, _) in old_properties.0.iter().filter(|&(k, _)| !self.0.contains_key(k)) {
+                    backend.set_property(parent, k, &AttrValue::Absent);
+                }
+            }
+            self
+        }
+    }
+
+    impl DOMRemove for Properties {
+        fn remove<B: Backend>(self, backend: &B, parent: &B::Node) {
             for (k, _) in self.0.iter() {
-                parent.remove_attribute(k);
+                backend.set_property(parent, k, &AttrValue::Absent);
             }
         }
     }
 
     impl DOMPatch<Events> for Events {
-        fn patch(mut self, parent: &Element, _: Option<&Node>, old_vnode: Option<Events>, _: RenderRequestSender) -> Self {
+        fn patch<B: Backend>(mut self, backend: &B, parent: &B::Node, _: Option<&B::Node>, old_vnode: Option<Events>, _: RenderRequestSender) -> Self {
             // Remove older events because their is no way for Eq between two events.
-            old_vnode.remove(parent);
+            old_vnode.remove(backend, parent);
             for ev in self.0.iter_mut() {
-                ev.attach(parent);
+                backend.add_listener(parent, &mut **ev);
             }
             self
         }
     }
 
     impl DOMRemove for Events {
-        fn remove(mut self, _: &Element) {
+        fn remove<B: Backend>(mut self, backend: &B, parent: &B::Node) {
             for ev in self.0.iter_mut() {
-                ev.detach();
+                backend.remove_listener(parent, &mut **ev);
             }
         }
     }
 
     impl DOMNode for VElement {
-        fn dom_node(&self) -> Option<Node> {
-            self.dom_ref.clone().map(|it| it.into())
+        fn dom_node<B: Backend>(&self) -> Option<B::Node> {
+            self.dom_ref.as_ref().map(|it| it.node::<B>().clone())
         }
     }
 
     impl DOMRender for VElement {
-        fn dom_render(&mut self, parent: &Element, next: Option<&Node>, render_req: RenderRequestSender) {
-            if let Some(ref mut child) = self.child {
-                child.dom_render(parent, next, render_req);
+        fn dom_render<B: Backend>(&mut self, backend: &B, _: &B::Node, _: Option<&B::Node>, render_req: RenderRequestSender) {
+            let dom_ref = self.dom_ref.clone().expect("Cannot render a non-existent element.");
+            let el = dom_ref.node::<B>();
+            let mut next_node = None;
+            for child in self.children.iter_mut().rev() {
+                child.dom_render(backend, el, next_node.as_ref(), render_req.clone());
+                next_node = child.dom_node::<B>().or(next_node);
             }
         }
     }
@@ -3,8 +3,15 @@ use std::fmt::Display;
 use std::fmt::{Formatter, self};
 use indexmap::IndexMap;
 use CowStr;
+use namespace::{Namespace, InheritNamespace};
 #[cfg(not(target_arch = "wasm32"))]
-use traits::ServerRender;
+use traits::{ServerRender, ServerStream, CollectStates, ServerLoad};
+#[cfg(not(target_arch = "wasm32"))]
+use std::task::{Context as TaskContext, Poll};
+#[cfg(not(target_arch = "wasm32"))]
+use state::{self, SavedStates};
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::{self, Write};
 
 type Key = CowStr;
 
@@ -20,7 +27,6 @@ impl VList {
         }
     }
 
-    #[cfg(target_arch = "wasm32")]
     fn position(&self, key: &str) -> Option<usize> {
         self.children.iter().position(|(k, _)| k == key)
     }
@@ -54,6 +60,14 @@ impl From<Vec<VNode>> for VList {
     }
 }
 
+impl InheritNamespace for VList {
+    fn inherit_namespace(&mut self, ns: Namespace) {
+        for (_, child) in self.children.iter_mut() {
+            child.inherit_namespace(ns);
+        }
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 impl ServerRender for VList {
     fn server_render(&mut self) {
@@ -63,47 +77,81 @@ impl ServerRender for VList {
     }
 }
 
-#[cfg(target_arch = "wasm32")]
-mod wasm {
+/// The children are polled together, so that their loads are resolved concurrently.
+#[cfg(not(target_arch = "wasm32"))]
+impl ServerLoad for VList {
+    fn poll_load(&mut self, cx: &mut TaskContext) -> Poll<()> {
+        let mut loaded = Poll::Ready(());
+        for (_, child) in self.children.iter_mut() {
+            if child.poll_load(cx).is_pending() {
+                loaded = Poll::Pending;
+            }
+        }
+        loaded
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ServerStream for VList {
+    fn server_stream(self, writer: &mut Write) -> io::Result<()> {
+        for (i, (_, child)) in self.children.into_iter().enumerate() {
+            state::streaming_child(i, || child.server_stream(writer))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl CollectStates for VList {
+    fn collect_states(&self, path: &mut Vec<usize>, states: &mut SavedStates) {
+        for (i, child) in self.children.values().enumerate() {
+            path.push(i);
+            child.collect_states(path, states);
+            path.pop();
+        }
+    }
+}
+
+mod dom {
     use super::VList;
     use vdiff::{DOMPatch, DOMRemove};
-    use stdweb::web::Element;
     use vdiff::DOMReorder;
     use vdiff::DOMNode;
-    use stdweb::web::Node;
     use CowStr;
     use traits::DOMRender;
+    use backend::Backend;
     use events::RenderRequestSender;
     use indexmap::IndexMap;
+    use hydrate::{DOMHydrate, Hydration};
 
     impl DOMPatch<VList> for VList {
-        fn patch(mut self, parent: &Element, next: Option<&Node>, old_vnodes: Option<VList>, render_req: RenderRequestSender) -> Self {
+        fn patch<B: Backend>(mut self, backend: &B, parent: &B::Node, next: Option<&B::Node>, old_vnodes: Option<VList>, render_req: RenderRequestSender) -> Self {
             if let Some(mut old_vnodes) = old_vnodes {
                 let old_children_pos: IndexMap<CowStr, usize> = old_vnodes.children.iter()
                     .enumerate()
                     .map(|(pos, (k, _))| (k.clone(), pos))
                     .collect();
-                let mut next_node = next.map(|it| it.clone());
+                let mut next_node = next.cloned();
                 let mut children = IndexMap::new();
                 for (k, v) in self.children.into_iter().rev() {
-                    let v = if let Some(mut pre_vnode) = old_vnodes.children.swap_remove(&k) {
+                    let v = if let Some(pre_vnode) = old_vnodes.children.swap_remove(&k) {
                         // Patch if any old VNode found
-                        v.patch(parent, next_node.as_ref(), Some(pre_vnode), render_req.clone())
+                        v.patch(backend, parent, next_node.as_ref(), Some(pre_vnode), render_req.clone())
                     } else {
-                        v.patch(parent, next_node.as_ref(), None, render_req.clone())
+                        v.patch(backend, parent, next_node.as_ref(), None, render_req.clone())
                     };
-                    next_node = v.dom_node();
+                    next_node = v.dom_node::<B>().or(next_node);
                     children.insert(k, v);
                 }
                 self.children = children.into_iter().rev().collect();
                 if has_dirty_order(&self, &old_children_pos) {
-                    update_dom_positions(&self, parent, &old_children_pos);
+                    update_dom_positions(&self, backend, parent, next, &old_children_pos);
                 }
-                remove_old_vnodes(old_vnodes, parent);
+                remove_old_vnodes(old_vnodes, backend, parent);
             } else {
                 let mut children = IndexMap::new();
                 for (k, v) in self.children {
-                    let v = v.patch(parent, None, None, render_req.clone());
+                    let v = v.patch(backend, parent, next, None, render_req.clone());
                     children.insert(k, v);
                 }
                 self.children = children;
@@ -112,6 +160,21 @@ mod wasm {
         }
     }
 
+    impl DOMHydrate for VList {
+        fn hydrate<B: Backend>(mut self, backend: &B, hydration: &mut Hydration<B>, render_req: RenderRequestSender) -> Self {
+            self.children = self.children.into_iter()
+                .enumerate()
+                .map(|(i, (k, v))| {
+                    hydration.enter(i);
+                    let v = v.hydrate(backend, hydration, render_req.clone());
+                    hydration.leave();
+                    (k, v)
+                })
+                .collect();
+            self
+        }
+    }
+
     fn has_dirty_order(new_vnodes: &VList, old_nodes: &IndexMap<CowStr, usize>) -> bool {
         let mut old_last_position = 0;
         for (k, _) in new_vnodes.children.iter() {
@@ -130,63 +193,68 @@ mod wasm {
         false
     }
 
-    fn update_dom_positions(new_vnodes: &VList, parent: &Element, old_vnodes: &IndexMap<CowStr, usize>) {
+    fn update_dom_positions<B: Backend>(new_vnodes: &VList, backend: &B, parent: &B::Node, next: Option<&B::Node>, old_vnodes: &IndexMap<CowStr, usize>) {
         let mut next_key = None;
         for (k, new_node) in new_vnodes.children.iter().rev() {
             let new_pos = new_vnodes.position(k);
-            let old_pos = old_vnodes.get(k).map(|it| *it);
-            if old_pos.is_none() {
-                // It is a new node and already inserted to the write place.
-            } else if new_pos.unwrap() != old_pos.unwrap() {
-                if let Some(next_key) = next_key {
-                    let next_vnode = new_vnodes.children.get(next_key).unwrap();
-                    new_node.move_before(parent, &next_vnode.dom_node().unwrap());
+            let old_pos = old_vnodes.get(k).cloned();
+            // A new node is already inserted to the right place.
+            if old_pos.is_some() && new_pos != old_pos {
+                let next_node = next_key
+                    .and_then(|next_key| new_vnodes.children.get(next_key).unwrap().dom_node::<B>())
+                    .or(next.cloned());
+                if let Some(next_node) = next_node {
+                    new_node.move_before(backend, parent, &next_node);
                 } else {
-                    new_node.move_to_last(parent);
+                    new_node.move_to_last(backend, parent);
                 }
             }
             next_key = Some(k);
         }
     }
 
-    fn remove_old_vnodes(old_vnodes: VList, parent: &Element) {
+    fn remove_old_vnodes<B: Backend>(old_vnodes: VList, backend: &B, parent: &B::Node) {
         for (_, v) in old_vnodes.children {
-            v.remove(parent);
+            v.remove(backend, parent);
         }
     }
 
     impl DOMRemove for VList {
-        fn remove(self, parent: &Element) {
+        fn remove<B: Backend>(self, backend: &B, parent: &B::Node) {
             for (_, child) in self.children {
-                child.remove(parent);
+                child.remove(backend, parent);
             }
         }
     }
 
     impl DOMReorder for VList {
-        fn move_to_last(&self, parent: &Element) {
+        fn move_to_last<B: Backend>(&self, backend: &B, parent: &B::Node) {
             for (_, v) in self.children.iter() {
-                v.move_to_last(parent);
+                v.move_to_last(backend, parent);
             }
         }
 
-        fn move_before(&self, parent: &Element, next: &Node) {
+        fn move_before<B: Backend>(&self, backend: &B, parent: &B::Node, next: &B::Node) {
             for (_, v) in self.children.iter() {
-                v.move_before(parent, next);
+                v.move_before(backend, parent, next);
             }
         }
     }
 
     impl DOMNode for VList {
-        fn dom_node(&self) -> Option<Node> {
-            self.children.iter().next().and_then(|it| it.1.dom_node())
+        fn dom_node<B: Backend>(&self) -> Option<B::Node> {
+            self.children.iter()
+                .filter_map(|(_, v)| v.dom_node::<B>())
+                .next()
         }
     }
 
     impl DOMRender for VList {
-        fn dom_render(&mut self, parent: &Element, next: Option<&Node>, render_req: RenderRequestSender) {
-            for (_, child) in self.children.iter_mut() {
-                child.dom_render(parent, next, render_req.clone());
+        fn dom_render<B: Backend>(&mut self, backend: &B, parent: &B::Node, next: Option<&B::Node>, render_req: RenderRequestSender) {
+            let mut next_node = next.cloned();
+            for (_, child) in self.children.iter_mut().rev() {
+                child.dom_render(backend, parent, next_node.as_ref(), render_req.clone());
+                next_node = child.dom_node::<B>().or(next_node);
             }
         }
     }
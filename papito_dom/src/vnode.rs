@@ -1,18 +1,26 @@
 use velement::VElement;
 use vlist::VList;
 use vtext::VText;
+use vraw::VRaw;
 use std::fmt::Display;
 use std::fmt::{Formatter, self};
 use vcomponent::VComponent;
 #[cfg(not(target_arch = "wasm32"))]
-use traits::ServerRender;
+use traits::{ServerRender, ServerStream, CollectStates, ServerLoad};
+#[cfg(not(target_arch = "wasm32"))]
+use std::task::{Context as TaskContext, Poll};
+#[cfg(not(target_arch = "wasm32"))]
+use state::SavedStates;
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::{self, Write};
 
 #[derive(Debug, Eq, PartialEq)]
 pub enum VNode {
     Text(VText),
     Element(VElement),
     List(VList),
-    Component(VComponent)
+    Component(VComponent),
+    Raw(VRaw)
 }
 
 impl VNode {
@@ -27,7 +35,8 @@ impl Display for VNode {
             VNode::Text(ref text) => write!(f, "{}", text),
             VNode::Element(ref element) => write!(f, "{}", element),
             VNode::List(ref list) => write!(f, "{}", list),
-            VNode::Component(ref component) => write!(f, "{}", component)
+            VNode::Component(ref component) => write!(f, "{}", component),
+            VNode::Raw(ref raw) => write!(f, "{}", raw)
         }
     }
 }
@@ -46,6 +55,7 @@ impl_conversion_to_vnode!(Text, VText);
 impl_conversion_to_vnode!(Element, VElement);
 impl_conversion_to_vnode!(List, VList);
 impl_conversion_to_vnode!(Component, VComponent);
+impl_conversion_to_vnode!(Raw, VRaw);
 
 #[cfg(not(target_arch = "wasm32"))]
 impl ServerRender for VNode {
@@ -54,32 +64,68 @@ impl ServerRender for VNode {
             VNode::Component(ref mut component) => component.server_render(),
             VNode::List(ref mut list) => list.server_render(),
             VNode::Element(ref mut element) => element.server_render(),
-            VNode::Text(_) => {}
+            VNode::Text(_) | VNode::Raw(_) => {}
         }
     }
 }
 
-#[cfg(target_arch = "wasm32")]
-mod wasm {
+#[cfg(not(target_arch = "wasm32"))]
+impl ServerLoad for VNode {
+    fn poll_load(&mut self, cx: &mut TaskContext) -> Poll<()> {
+        match *self {
+            VNode::Component(ref mut component) => component.poll_load(cx),
+            VNode::List(ref mut list) => list.poll_load(cx),
+            VNode::Element(ref mut element) => element.poll_load(cx),
+            VNode::Text(_) | VNode::Raw(_) => Poll::Ready(())
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ServerStream for VNode {
+    fn server_stream(self, writer: &mut Write) -> io::Result<()> {
+        match self {
+            VNode::Component(component) => component.server_stream(writer),
+            VNode::List(list) => list.server_stream(writer),
+            VNode::Element(element) => element.server_stream(writer),
+            VNode::Text(text) => write!(writer, "{}", text),
+            VNode::Raw(raw) => write!(writer, "{}", raw)
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl CollectStates for VNode {
+    fn collect_states(&self, path: &mut Vec<usize>, states: &mut SavedStates) {
+        match *self {
+            VNode::Component(ref component) => component.collect_states(path, states),
+            VNode::List(ref list) => list.collect_states(path, states),
+            VNode::Element(ref element) => element.collect_states(path, states),
+            VNode::Text(_) | VNode::Raw(_) => {}
+        }
+    }
+}
+
+mod dom {
     use vdiff::{DOMPatch, DOMRemove};
-    use stdweb::web::Element;
     use super::VNode;
     use vdiff::DOMReorder;
     use vdiff::DOMNode;
-    use stdweb::web::Node;
     use traits::DOMRender;
+    use backend::Backend;
     use events::RenderRequestSender;
+    use hydrate::{DOMHydrate, Hydration};
 
     macro_rules! match_for_vnode_patch {
-        ($against:ident, $parent:ident, $next:ident, $old_vnode:ident, $render_req:ident, [$( $variant:ident ),*] ) => {
+        ($against:ident, $backend:ident, $parent:ident, $next:ident, $old_vnode:ident, $render_req:ident, [$( $variant:ident ),*] ) => {
             match $against {
                 $(
                     VNode::$variant(node_like) => {
                         if let Some(VNode::$variant(old_node_like)) = $old_vnode {
-                            node_like.patch($parent, $next, Some(old_node_like), $render_req).into()
+                            node_like.patch($backend, $parent, $next, Some(old_node_like), $render_req).into()
                         } else {
-                            $old_vnode.remove($parent);
-                            node_like.patch($parent, $next, None, $render_req).into()
+                            $old_vnode.remove($backend, $parent);
+                            node_like.patch($backend, $parent, $next, None, $render_req).into()
                         }
                     }
                 )*
@@ -88,60 +134,76 @@ mod wasm {
     }
 
     impl DOMPatch<VNode> for VNode {
-        fn patch(self, parent: &Element, next: Option<&Node>, old_vnode: Option<VNode>, render_req: RenderRequestSender) -> Self {
-            match_for_vnode_patch!(self, parent, next, old_vnode, render_req, [Text, Element, List, Component])
+        fn patch<B: Backend>(self, backend: &B, parent: &B::Node, next: Option<&B::Node>, old_vnode: Option<VNode>, render_req: RenderRequestSender) -> Self {
+            match_for_vnode_patch!(self, backend, parent, next, old_vnode, render_req, [Text, Element, List, Component, Raw])
+        }
+    }
+
+    impl DOMHydrate for VNode {
+        fn hydrate<B: Backend>(self, backend: &B, hydration: &mut Hydration<B>, render_req: RenderRequestSender) -> Self {
+            match self {
+                VNode::Text(text) => VNode::Text(text.hydrate(backend, hydration, render_req)),
+                VNode::Element(element) => VNode::Element(element.hydrate(backend, hydration, render_req)),
+                VNode::List(list) => VNode::List(list.hydrate(backend, hydration, render_req)),
+                VNode::Component(component) => VNode::Component(component.hydrate(backend, hydration, render_req)),
+                VNode::Raw(raw) => VNode::Raw(raw.hydrate(backend, hydration, render_req))
+            }
         }
     }
 
     impl DOMRemove for VNode {
-        fn remove(self, parent: &Element) {
+        fn remove<B: Backend>(self, backend: &B, parent: &B::Node) {
             match self {
-                VNode::Text(text) => text.remove(parent),
-                VNode::Element(element) => element.remove(parent),
-                VNode::List(list) => list.remove(parent),
-                VNode::Component(component) => component.remove(parent)
+                VNode::Text(text) => text.remove(backend, parent),
+                VNode::Element(element) => element.remove(backend, parent),
+                VNode::List(list) => list.remove(backend, parent),
+                VNode::Component(component) => component.remove(backend, parent),
+                VNode::Raw(raw) => raw.remove(backend, parent)
             }
         }
     }
 
     impl DOMReorder for VNode {
-        fn move_to_last(&self, parent: &Element) {
+        fn move_to_last<B: Backend>(&self, backend: &B, parent: &B::Node) {
             match *self {
-                VNode::Text(ref text) => text.move_to_last(parent),
-                VNode::Element(ref element) => element.move_to_last(parent),
-                VNode::List(ref list) => list.move_to_last(parent),
-                VNode::Component(ref component) => component.move_to_last(parent)
+                VNode::Text(ref text) => text.move_to_last(backend, parent),
+                VNode::Element(ref element) => element.move_to_last(backend, parent),
+                VNode::List(ref list) => list.move_to_last(backend, parent),
+                VNode::Component(ref component) => component.move_to_last(backend, parent),
+                VNode::Raw(ref raw) => raw.move_to_last(backend, parent)
             }
         }
 
-        fn move_before(&self, parent: &Element, next: &Node) {
+        fn move_before<B: Backend>(&self, backend: &B, parent: &B::Node, next: &B::Node) {
             match *self {
-                VNode::Text(ref text) => text.move_before(parent, next),
-                VNode::Element(ref element) => element.move_before(parent, next),
-                VNode::List(ref list) => list.move_before(parent, next),
-                VNode::Component(ref component) => component.move_before(parent, next)
+                VNode::Text(ref text) => text.move_before(backend, parent, next),
+                VNode::Element(ref element) => element.move_before(backend, parent, next),
+                VNode::List(ref list) => list.move_before(backend, parent, next),
+                VNode::Component(ref component) => component.move_before(backend, parent, next),
+                VNode::Raw(ref raw) => raw.move_before(backend, parent, next)
             }
         }
     }
 
     impl DOMNode for VNode {
-        fn dom_node(&self) -> Option<Node> {
+        fn dom_node<B: Backend>(&self) -> Option<B::Node> {
             match *self {
-                VNode::Text(ref text) => text.dom_node(),
-                VNode::Element(ref element) => element.dom_node(),
-                VNode::List(ref list) => list.dom_node(),
-                VNode::Component(ref component) => component.dom_node()
+                VNode::Text(ref text) => text.dom_node::<B>(),
+                VNode::Element(ref element) => element.dom_node::<B>(),
+                VNode::List(ref list) => list.dom_node::<B>(),
+                VNode::Component(ref component) => component.dom_node::<B>(),
+                VNode::Raw(ref raw) => raw.dom_node::<B>()
             }
         }
     }
 
     impl DOMRender for VNode {
-        fn dom_render(&mut self, parent: &Element, next: Option<&Node>, render_req: RenderRequestSender) {
+        fn dom_render<B: Backend>(&mut self, backend: &B, parent: &B::Node, next: Option<&B::Node>, render_req: RenderRequestSender) {
             match *self {
-                VNode::Component(ref mut component) => component.dom_render(parent, next, render_req),
-                VNode::List(ref mut list) => list.dom_render(parent, next, render_req),
-                VNode::Element(ref mut element) => element.dom_render(parent, next, render_req),
-                VNode::Text(_) => {}
+                VNode::Component(ref mut component) => component.dom_render(backend, parent, next, render_req),
+                VNode::List(ref mut list) => list.dom_render(backend, parent, next, render_req),
+                VNode::Element(ref mut element) => element.dom_render(backend, parent, next, render_req),
+                VNode::Text(_) | VNode::Raw(_) => {}
             }
         }
     }
@@ -0,0 +1,273 @@
+use CowStr;
+use std::fmt::{self, Formatter};
+use std::fmt::Display;
+use vdiff::DOMRef;
+use velement::is_void_element;
+use escape::unescape;
+
+/// A trusted html fragment which is rendered as is, without any escaping. Never use it with
+/// user provided content.
+#[derive(Debug, Eq, PartialEq)]
+pub struct VRaw {
+    html: CowStr,
+    dom_refs: Vec<DOMRef>,
+}
+
+impl VRaw {
+    pub fn new(html: CowStr) -> VRaw {
+        VRaw {
+            html,
+            dom_refs: vec![],
+        }
+    }
+}
+
+impl Display for VRaw {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.html)
+    }
+}
+
+impl<T: Into<CowStr>> From<T> for VRaw {
+    fn from(item: T) -> Self {
+        VRaw::new(item.into())
+    }
+}
+
+/// The number of the top-level nodes the `html` is parsed into by the browser, along with the
+/// content of the last one if it is a text. It is only scanned, with the end tags which are
+/// commonly omitted, e.g. of `<p>` and `<li>`, inferred as the browser does.
+fn top_level_nodes(html: &str) -> (usize, Option<String>) {
+    let mut count = 0;
+    let mut open: Vec<String> = vec![];
+    let mut last_text: Option<String> = None;
+    let mut rest = html;
+    while !rest.is_empty() {
+        let start = markup_start(rest).unwrap_or(rest.len());
+        if open.is_empty() && start > 0 {
+            match last_text {
+                Some(ref mut text) => text.push_str(&rest[..start]),
+                None => {
+                    count += 1;
+                    last_text = Some(rest[..start].to_string());
+                }
+            }
+        }
+        rest = &rest[start..];
+        if rest.is_empty() {
+            break;
+        }
+        if rest.starts_with("</") {
+            let tag_end = tag_end(rest);
+            let name = tag_name(&rest[2..tag_end]);
+            // A stray end tag is ignored
+            if let Some(i) = open.iter().rposition(|it| *it == name) {
+                open.truncate(i);
+            }
+            rest = &rest[(tag_end + 1).min(rest.len())..];
+            continue;
+        }
+        if rest.starts_with("<!--") {
+            rest = skip_past(&rest[4..], "-->");
+        } else if rest.starts_with("<!") || rest.starts_with("<?") {
+            rest = skip_past(rest, ">");
+        } else {
+            let tag_end = tag_end(rest);
+            let tag = &rest[1..tag_end];
+            let name = tag_name(tag);
+            let is_self_closing = tag.ends_with('/');
+            rest = &rest[(tag_end + 1).min(rest.len())..];
+            close_implied(&mut open, &name);
+            if open.is_empty() {
+                count += 1;
+                last_text = None;
+            }
+            if RAW_TEXT_ELEMENTS.contains(&&*name) {
+                let end = rest.to_ascii_lowercase().find(&format!("</{}", name));
+                rest = skip_past(&rest[end.unwrap_or(rest.len())..], ">");
+            } else if !is_self_closing && !is_void_element(&name) {
+                open.push(name);
+            }
+            continue;
+        }
+        if open.is_empty() {
+            count += 1;
+            last_text = None;
+        }
+    }
+    (count, last_text.map(|it| unescape(&it)))
+}
+
+/// The lowercase name of the tag, from its content after the `<` or `</`.
+fn tag_name(tag: &str) -> String {
+    tag.split(|ch: char| ch.is_whitespace() || ch == '/')
+        .next()
+        .unwrap_or("")
+        .to_ascii_lowercase()
+}
+
+/// The elements which close an open `<p>` without its end tag.
+const CLOSING_P: &[&str] = &[
+    "address", "article", "aside", "blockquote", "details", "dialog", "div", "dl", "fieldset",
+    "figcaption", "figure", "footer", "form", "h1", "h2", "h3", "h4", "h5", "h6", "header",
+    "hgroup", "hr", "main", "menu", "nav", "ol", "p", "pre", "section", "table", "ul",
+];
+
+/// Closes the open elements whose end tags are implied by the start tag `name`, up to the
+/// elements which bound them, e.g. the `<li>` of the same list.
+fn close_implied(open: &mut Vec<String>, name: &str) {
+    let (closed, bounds): (&[&str], &[&str]) = match name {
+        "li" => (&["li"], &["ul", "ol", "menu"]),
+        "dt" | "dd" => (&["dt", "dd"], &["dl"]),
+        "option" => (&["option"], &["select", "datalist", "optgroup"]),
+        "optgroup" => (&["option", "optgroup"], &["select"]),
+        "tr" => (&["tr"], &["table", "thead", "tbody", "tfoot"]),
+        "td" | "th" => (&["td", "th"], &["tr", "table"]),
+        "thead" | "tbody" | "tfoot" => (&["thead", "tbody", "tfoot"], &["table"]),
+        _ if CLOSING_P.contains(&name) => (&["p"], &["button", "table", "td", "th", "caption", "object", "template"]),
+        _ => return
+    };
+    let found = open.iter()
+        .rev()
+        .take_while(|it| !bounds.contains(&&***it))
+        .position(|it| closed.contains(&&**it));
+    if let Some(i) = found {
+        let len = open.len();
+        open.truncate(len - 1 - i);
+    }
+}
+
+/// The elements whose content is never parsed as html.
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style", "textarea", "title", "xmp"];
+
+/// The position of the next tag, comment or doctype.
+fn markup_start(html: &str) -> Option<usize> {
+    html.match_indices('<')
+        .map(|(i, _)| i)
+        .find(|&i| {
+            let mut next = html[i + 1..].chars();
+            match next.next() {
+                Some('/') => next.next().map(|ch| ch.is_ascii_alphabetic()).unwrap_or(false),
+                Some('!') | Some('?') => true,
+                Some(ch) => ch.is_ascii_alphabetic(),
+                None => false
+            }
+        })
+}
+
+/// The position of the `>` closing the tag, skipping the ones within the quoted attribute values.
+fn tag_end(tag: &str) -> usize {
+    let mut quote = None;
+    for (i, ch) in tag.char_indices() {
+        match (quote, ch) {
+            (Some(q), _) if q == ch => quote = None,
+            (Some(_), _) => {}
+            (None, '"') | (None, '\'') => quote = Some(ch),
+            (None, '>') => return i,
+            _ => {}
+        }
+    }
+    tag.len()
+}
+
+fn skip_past<'a>(html: &'a str, end: &str) -> &'a str {
+    html.find(end)
+        .map(|i| &html[i + end.len()..])
+        .unwrap_or("")
+}
+
+mod dom {
+    use vdiff::{DOMPatch, DOMRemove, DOMRef};
+    use super::VRaw;
+    use vdiff::DOMReorder;
+    use vdiff::DOMNode;
+    use backend::Backend;
+    use events::RenderRequestSender;
+    use hydrate::{DOMHydrate, Hydration, Mismatch};
+    use super::top_level_nodes;
+
+    impl DOMPatch<VRaw> for VRaw {
+        fn patch<B: Backend>(mut self, backend: &B, parent: &B::Node, next: Option<&B::Node>, old_vnode: Option<VRaw>, _: RenderRequestSender) -> Self {
+            if let Some(old_vnode) = old_vnode {
+                if old_vnode.html == self.html {
+                    self.dom_refs = old_vnode.dom_refs;
+                    return self;
+                }
+                // Replace the older content in place
+                let next = old_vnode.dom_node::<B>().or(next.cloned());
+                self.dom_refs = insert_raw_html(&self, backend, parent, next.as_ref());
+                old_vnode.remove(backend, parent);
+            } else {
+                self.dom_refs = insert_raw_html(&self, backend, parent, next);
+            }
+            self
+        }
+    }
+
+    impl DOMHydrate for VRaw {
+        fn hydrate<B: Backend>(mut self, backend: &B, hydration: &mut Hydration<B>, _: RenderRequestSender) -> Self {
+            let (count, last_text) = top_level_nodes(&self.html);
+            let adopted: Vec<_> = (0..count)
+                .filter_map(|_| hydration.adopt_next())
+                .collect();
+            if adopted.len() == count {
+                // The trailing text is parsed along with the text of the next node by the browser
+                if let (Some(last), Some(own)) = (adopted.last(), last_text) {
+                    if let Some(found) = backend.text(last) {
+                        if found.len() > own.len() && found.starts_with(&own) {
+                            hydration.split_text(backend, last, &found, &own);
+                        }
+                    }
+                }
+                self.dom_refs = adopted.into_iter()
+                    .map(DOMRef::new::<B>)
+                    .collect();
+            } else {
+                hydration.report(Mismatch::Missing(format!("the raw html {:?}", self.html)));
+                let parent = hydration.parent().clone();
+                for node in adopted {
+                    backend.remove_child(&parent, &node);
+                }
+                self.dom_refs = insert_raw_html(&self, backend, &parent, hydration.peek());
+            }
+            self
+        }
+    }
+
+    fn insert_raw_html<B: Backend>(vraw: &VRaw, backend: &B, parent: &B::Node, next: Option<&B::Node>) -> Vec<DOMRef> {
+        backend.create_raw_html(&vraw.html).into_iter()
+            .map(|node| {
+                backend.insert(parent, &node, next);
+                DOMRef::new::<B>(node)
+            })
+            .collect()
+    }
+
+    impl DOMReorder for VRaw {
+        fn move_to_last<B: Backend>(&self, backend: &B, parent: &B::Node) {
+            for dom_ref in self.dom_refs.iter() {
+                backend.append_child(parent, dom_ref.node::<B>());
+            }
+        }
+
+        fn move_before<B: Backend>(&self, backend: &B, parent: &B::Node, next: &B::Node) {
+            for dom_ref in self.dom_refs.iter() {
+                backend.insert_before(parent, dom_ref.node::<B>(), next);
+            }
+        }
+    }
+
+    impl DOMRemove for VRaw {
+        fn remove<B: Backend>(self, backend: &B, parent: &B::Node) {
+            for dom_ref in self.dom_refs {
+                backend.remove_child(parent, dom_ref.node::<B>());
+            }
+        }
+    }
+
+    impl DOMNode for VRaw {
+        fn dom_node<B: Backend>(&self) -> Option<B::Node> {
+            self.dom_refs.first().map(|it| it.node::<B>().clone())
+        }
+    }
+}
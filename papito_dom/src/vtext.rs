@@ -1,34 +1,35 @@
 use CowStr;
 use std::fmt::{self, Formatter};
 use std::fmt::Display;
-#[cfg(target_arch = "wasm32")]
-use stdweb::web::TextNode;
+use vdiff::DOMRef;
+use escape::Escaped;
 
 #[derive(Debug, Eq, PartialEq)]
 pub struct VText {
     content: CowStr,
-    #[cfg(target_arch = "wasm32")]
-    dom_ref: Option<TextNode>,
+    dom_ref: Option<DOMRef>,
 }
 
 impl VText {
     pub fn new(content: CowStr) -> VText {
         VText {
             content,
-            #[cfg(target_arch = "wasm32")]
             dom_ref: None,
         }
     }
 
-    #[cfg(target_arch = "wasm32")]
-    pub fn dom_ref(&self) -> Option<&TextNode> {
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    pub fn dom_ref(&self) -> Option<&DOMRef> {
         self.dom_ref.as_ref()
     }
 }
 
 impl Display for VText {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        write!(f, "{}", self.content)
+        write!(f, "{}", Escaped(&self.content))
     }
 }
 
@@ -38,60 +39,70 @@ impl<T: Into<CowStr>> From<T> for VText {
     }
 }
 
-#[cfg(target_arch = "wasm32")]
-mod wasm {
-    use stdweb::web::{Element, document, INode};
-    use vdiff::{DOMPatch, DOMRemove};
+mod dom {
+    use vdiff::{DOMPatch, DOMRemove, DOMRef};
     use super::VText;
     use vdiff::DOMReorder;
     use vdiff::DOMNode;
-    use stdweb::web::Node;
+    use backend::Backend;
     use events::RenderRequestSender;
+    use hydrate::{DOMHydrate, Hydration};
 
     impl DOMPatch<VText> for VText {
-        fn patch(mut self, parent: &Element, next: Option<&Node>, old_vnode: Option<VText>, _: RenderRequestSender) -> Self {
+        fn patch<B: Backend>(mut self, backend: &B, parent: &B::Node, next: Option<&B::Node>, old_vnode: Option<VText>, _: RenderRequestSender) -> Self {
             if let Some(old_vnode) = old_vnode {
-                let text_node = old_vnode.dom_ref().unwrap().clone();
+                let dom_ref = old_vnode.dom_ref().unwrap().clone();
                 if old_vnode.content != self.content {
-                    text_node.set_text_content(&self.content);
+                    backend.set_text_content(dom_ref.node::<B>(), &self.content);
                 }
-                self.dom_ref = Some(text_node);
+                self.dom_ref = Some(dom_ref);
             } else {
-                let text_node = document().create_text_node(&self.content);
-                self.dom_ref = Some(text_node);
-                if let Some(next) = next {
-                    parent.insert_before(self.dom_ref().unwrap(), next).unwrap();
-                } else {
-                    parent.append_child(self.dom_ref().unwrap());
-                }
+                let text_node = backend.create_text(&self.content);
+                backend.insert(parent, &text_node, next);
+                self.dom_ref = Some(DOMRef::new::<B>(text_node));
             }
             self
         }
     }
 
+    impl DOMHydrate for VText {
+        fn hydrate<B: Backend>(mut self, backend: &B, hydration: &mut Hydration<B>, render_req: RenderRequestSender) -> Self {
+            if let Some(text_node) = hydration.next_text(backend, &self.content) {
+                self.dom_ref = Some(DOMRef::new::<B>(text_node));
+                return self;
+            }
+            if !self.content.is_empty() {
+                hydration.mismatched(backend, format!("the text {:?}", self.content));
+            }
+            let parent = hydration.parent().clone();
+            let next = hydration.peek().cloned();
+            self.patch(backend, &parent, next.as_ref(), None, render_req)
+        }
+    }
+
     impl DOMReorder for VText {
-        fn move_to_last(&self, parent: &Element) {
+        fn move_to_last<B: Backend>(&self, backend: &B, parent: &B::Node) {
             let dom_ref = self.dom_ref().expect("Cannot append previously non-existent text node.");
-            parent.append_child(dom_ref);
+            backend.append_child(parent, dom_ref.node::<B>());
         }
 
-        fn move_before(&self, parent: &Element, next: &Node) {
-            parent.insert_before(self.dom_ref().expect("Cannot insert previously non-existent text node."), next)
-                .unwrap();
+        fn move_before<B: Backend>(&self, backend: &B, parent: &B::Node, next: &B::Node) {
+            let dom_ref = self.dom_ref().expect("Cannot insert previously non-existent text node.");
+            backend.insert_before(parent, dom_ref.node::<B>(), next);
         }
     }
 
     impl DOMRemove for VText {
-        fn remove(mut self, parent: &Element) {
-            parent.remove_child(&self.dom_ref.take()
-                .expect("Cannot remove non-existent text node.")
-            ).unwrap();
+        fn remove<B: Backend>(mut self, backend: &B, parent: &B::Node) {
+            let dom_ref = self.dom_ref.take()
+                .expect("Cannot remove non-existent text node.");
+            backend.remove_child(parent, dom_ref.node::<B>());
         }
     }
 
     impl DOMNode for VText {
-        fn dom_node(&self) -> Option<Node> {
-            self.dom_ref.clone().map(|it| it.into())
+        fn dom_node<B: Backend>(&self) -> Option<B::Node> {
+            self.dom_ref.as_ref().map(|it| it.node::<B>().clone())
         }
     }
 }